@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+
+use crate::session::replay::{valid_replay_speed, MAX_REPLAY_SPEED};
+
+pub const USAGE: &str = "\
+Usage:
+  vx220-dashboard [--record [FILE]]
+      Run the dashboard on live RaceBox/ESP32 data, optionally recording the session.
+  vx220-dashboard replay FILE [--speed X] [--paused]
+      Run the dashboard on a recorded session instead of live data.
+  vx220-dashboard export csv|motec SESSION OUTPUT [--rate HZ]
+      Convert a recorded session to CSV or MoTeC .ld, resampled at HZ (default 20).
+  vx220-dashboard export gpx|kml SESSION OUTPUT
+      Export the GNSS track of a recorded session.";
+
+const DEFAULT_EXPORT_RATE_HZ: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Motec,
+    Gpx,
+    Kml,
+}
+
+/// What the binary was asked to do
+#[derive(Debug)]
+pub enum Command {
+    /// Live dashboard. `record` is `Some` when the session should be recorded,
+    /// with `None` inside meaning "pick a default file name".
+    Run { record: Option<Option<PathBuf>> },
+    /// Dashboard driven by a recorded session
+    Replay { path: PathBuf, speed: f32, paused: bool },
+    /// Convert a recorded session and exit
+    Export { format: ExportFormat, session: PathBuf, output: PathBuf, rate_hz: f32 },
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let args: Vec<String> = args.into_iter().collect();
+    match args.first().map(String::as_str) {
+        Some("replay") => parse_replay(&args[1..]),
+        Some("export") => parse_export(&args[1..]),
+        _ => parse_run(&args),
+    }
+}
+
+fn parse_run(args: &[String]) -> Result<Command, String> {
+    let mut record = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--record" => {
+                let path = args.get(i + 1).filter(|a| !a.starts_with("--")).map(PathBuf::from);
+                if path.is_some() {
+                    i += 1;
+                }
+                record = Some(path);
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+        i += 1;
+    }
+    Ok(Command::Run { record })
+}
+
+fn parse_replay(args: &[String]) -> Result<Command, String> {
+    let mut path = None;
+    let mut speed = 1.0;
+    let mut paused = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--speed" => {
+                let value = args.get(i + 1).ok_or("--speed needs a value")?;
+                speed = value.parse().map_err(|_| format!("Invalid speed: {}", value))?;
+                if !valid_replay_speed(speed) {
+                    return Err(format!("Speed must be above 0 and at most {}", MAX_REPLAY_SPEED));
+                }
+                i += 1;
+            }
+            "--paused" => paused = true,
+            other if other.starts_with("--") => return Err(format!("Unknown argument: {}", other)),
+            other => path = Some(PathBuf::from(other)),
+        }
+        i += 1;
+    }
+    let path = path.ok_or("replay needs a session file")?;
+    Ok(Command::Replay { path, speed, paused })
+}
+
+fn parse_export(args: &[String]) -> Result<Command, String> {
+    let mut positional = Vec::new();
+    let mut rate_hz = DEFAULT_EXPORT_RATE_HZ;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--rate" => {
+                let value = args.get(i + 1).ok_or("--rate needs a value")?;
+                rate_hz = value.parse().map_err(|_| format!("Invalid rate: {}", value))?;
+                i += 1;
+            }
+            other if other.starts_with("--") => return Err(format!("Unknown argument: {}", other)),
+            other => positional.push(other),
+        }
+        i += 1;
+    }
+    let [format, session, output] = positional[..] else {
+        return Err("export needs a format, a session file and an output file".to_string());
+    };
+    let format = match format {
+        "csv" => ExportFormat::Csv,
+        "motec" | "ld" => ExportFormat::Motec,
+        "gpx" => ExportFormat::Gpx,
+        "kml" => ExportFormat::Kml,
+        other => return Err(format!("Unknown export format: {}", other)),
+    };
+    Ok(Command::Export {
+        format,
+        session: PathBuf::from(session),
+        output: PathBuf::from(output),
+        rate_hz,
+    })
+}
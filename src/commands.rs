@@ -0,0 +1,628 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use log::LevelFilter;
+
+use crate::logging;
+use crate::session::recorder::SessionRecorder;
+use crate::session::replay::{valid_replay_speed, MAX_REPLAY_SPEED};
+use crate::timing::delta::{ReferenceLap, ReferenceSource};
+use crate::timing::geo::GeoPoint;
+use crate::timing::lap_timer::{format_delta, format_lap_time, TimingLine};
+use crate::timing::stint::format_stint_time;
+use crate::settings::{Settings, SpeedSource, SpeedUnit, MAX_FRICTION_LIMIT_G};
+use crate::telemetry::{ColorScheme, DashboardPage, DriveMode, SharedTelemetryState, TelemetryState};
+use crate::telemetry::tyres::CORNERS;
+use crate::telemetry::inputs::HISTORY_SPAN;
+
+const COMMAND_ADDR: &str = "127.0.0.1:7878";
+
+/// Start the command listener (in a background thread).
+///
+/// Each connection sends a single line command and receives a single line
+/// response, starting with `OK` or `ERR`.
+pub fn start_command_listener(telemetry_state: SharedTelemetryState) {
+    thread::spawn(move || {
+        let listener = TcpListener::bind(COMMAND_ADDR).expect("Failed to bind TCP listener");
+        for stream in listener.incoming().flatten() {
+            handle_command(stream, &telemetry_state);
+        }
+    });
+}
+
+fn handle_command(mut stream: TcpStream, telemetry_state: &SharedTelemetryState) {
+    let reader = BufReader::new(stream.try_clone().unwrap());
+    if let Some(Ok(cmd)) = reader.lines().next() {
+        let mut state = telemetry_state.blocking_lock();
+        let tokens: Vec<_> = cmd.split_whitespace().collect();
+        let response = match execute(&mut state, &tokens) {
+            Ok(message) => format!("{}\n", message),
+            Err(error) => format!("ERR {}\n", error),
+        };
+        let _ = stream.write_all(response.as_bytes());
+    }
+}
+
+fn execute(state: &mut TelemetryState, tokens: &[&str]) -> Result<String, String> {
+    match tokens {
+        ["set_mode", mode] => match *mode {
+            "Road" => state.set_drive_mode(DriveMode::Road),
+            "Track" => state.set_drive_mode(DriveMode::Track),
+            _ => return Err(format!("invalid mode: {}", mode)),
+        },
+        ["set_scheme", scheme] => match *scheme {
+            "Light" => state.set_color_scheme(ColorScheme::Light),
+            "Dark" => state.set_color_scheme(ColorScheme::Dark),
+            _ => return Err(format!("invalid scheme: {}", scheme)),
+        },
+        ["page", page] => match *page {
+            "main" => state.set_page(DashboardPage::Main),
+            "tyres" => state.set_page(DashboardPage::Tyres),
+            "map" => state.set_page(DashboardPage::Map),
+            "diagnostics" => state.set_page(DashboardPage::Diagnostics),
+            _ => return Err(format!("invalid page: {}", page)),
+        },
+        ["health"] => {
+            let now = state.now();
+            return Ok(format!(
+                "OK racebox {} | esp32 {}",
+                state.health.racebox.summary(now),
+                state.health.esp32.summary(now),
+            ));
+        }
+        ["mark"] => state.ingest_marker(),
+        ["record", "start"] => return start_recording(state, SessionRecorder::default_path()),
+        ["record", "start", path] => return start_recording(state, path.into()),
+        ["record", "stop"] => state.stop_recording(),
+        ["record", "status"] => {
+            return Ok(match &state.recorder {
+                Some(recorder) => format!("OK recording {} records={}", recorder.path().display(), recorder.records_written()),
+                None => "OK idle".to_string(),
+            });
+        }
+        ["lap_line"] => {
+            let timer = state.lap_timer.as_ref().ok_or("no start/finish line set")?;
+            let line = timer.start_finish();
+            return Ok(format!(
+                "OK {:.7} {:.7} {:.7} {:.7} heading={}",
+                line.a.lat,
+                line.a.lon,
+                line.b.lat,
+                line.b.lon,
+                line.heading_deg.map(|h| h.to_string()).unwrap_or_else(|| "any".to_string()),
+            ));
+        }
+        ["lap_line", "clear"] => state.clear_start_finish_line(),
+        ["lap_line", coords @ ..] => state.set_start_finish_line(parse_timing_line(coords)?),
+        ["split_line", "add", coords @ ..] => state.add_split_line(parse_timing_line(coords)?)?,
+        ["split_line", "clear"] => state.clear_split_lines(),
+        ["sectors"] => {
+            let timer = state.lap_timer.as_ref().ok_or("no start/finish line set")?;
+            let last_split = match timer.last_split() {
+                Some(split) => format!(
+                    "L{}S{} {} {}",
+                    split.lap,
+                    split.sector + 1,
+                    format_lap_time(split.time),
+                    split.delta_s().map(format_delta).unwrap_or_else(|| "-".to_string())
+                ),
+                None => "-".to_string(),
+            };
+            return Ok(format!(
+                "OK current={} best={} theoretical={} last_split={}",
+                format_sectors(timer.current_sectors()),
+                format_sectors(timer.best_sectors()),
+                timer.theoretical_best().map(format_lap_time).unwrap_or_else(|| "-".to_string()),
+                last_split,
+            ));
+        }
+        ["laps"] => {
+            let timer = state.lap_timer.as_ref().ok_or("no start/finish line set")?;
+            let fmt = |lap: Option<std::time::Duration>| lap.map(format_lap_time).unwrap_or_else(|| "-".to_string());
+            let times: Vec<String> = timer
+                .laps()
+                .iter()
+                .map(|lap| {
+                    let pit = if lap.pit { "P" } else { "" };
+                    match lap.sectors.len() {
+                        1 => format!("{}{}", format_lap_time(lap.time), pit),
+                        _ => format!("{}{}({})", format_lap_time(lap.time), pit, format_sectors(&lap.sectors)),
+                    }
+                })
+                .collect();
+            return Ok(format!(
+                "OK laps={} current={} last={} best={} times={}",
+                timer.lap_count(),
+                fmt(timer.current_lap_time()),
+                fmt(timer.last_lap().map(|lap| lap.time)),
+                fmt(timer.best_lap().map(|lap| lap.time)),
+                times.join(","),
+            ));
+        }
+        ["track"] => {
+            let detection = if state.track_detection { "auto" } else { "manual" };
+            return Ok(match &state.active_track {
+                Some(track) => format!(
+                    "OK name=\"{}\" length={:.0}m direction={} splits={} pit_lane={} detection={}",
+                    track.name,
+                    track.length_m,
+                    track.direction,
+                    track.splits.len(),
+                    if track.pit_lane.is_empty() { "no" } else { "yes" },
+                    detection,
+                ),
+                None => format!("OK none detection={}", detection),
+            });
+        }
+        ["track", "auto"] => state.enable_track_detection(),
+        ["track", "select", name @ ..] if !name.is_empty() => {
+            let name = name.join(" ");
+            let track = state.tracks.find(&name).cloned().ok_or(format!("unknown track: {}", name))?;
+            state.track_detection = false;
+            state.select_track(track);
+        }
+        ["learn", "start", name @ ..] if !name.is_empty() => state.start_learning_track(name.join(" ")),
+        ["learn", "mark"] => {
+            if state.track_learner.is_none() {
+                return Err("not learning a track".to_string());
+            }
+            state.ingest_marker();
+        }
+        ["learn", "cancel"] => state.track_learner = None,
+        ["learn", "status"] => {
+            return Ok(match &state.track_learner {
+                Some(learner) => match learner.distance_m() {
+                    Some(distance) => format!("OK learning \"{}\" distance={:.0}m", learner.name(), distance),
+                    None => format!("OK learning \"{}\" waiting for start/finish mark", learner.name()),
+                },
+                None => "OK idle".to_string(),
+            });
+        }
+        ["pit"] => {
+            let monitor = state.pit_monitor.as_ref().ok_or("no pit lane at the active track")?;
+            let stops: Vec<String> = monitor
+                .stops()
+                .iter()
+                .map(|stop| {
+                    format!(
+                        "{}:{:.1}s({:.1}s stationary, max {:.0}km/h)",
+                        stop.number,
+                        stop.lane_time.as_secs_f32(),
+                        stop.stationary_time.as_secs_f32(),
+                        stop.max_speed_kph
+                    )
+                })
+                .collect();
+            return Ok(format!(
+                "OK in_lane={} limit={:.0} speeding={} stops={}",
+                if monitor.in_pit_lane() { "yes" } else { "no" },
+                monitor.speed_limit_kph(),
+                if monitor.speeding() { "yes" } else { "no" },
+                stops.join(",")
+            ));
+        }
+        ["stint"] => {
+            let now = state.now();
+            let stint = &state.stint;
+            let fuel = |value: Option<f32>, decimals: usize| value.map_or("-".to_string(), |v| format!("{:.*}", decimals, v));
+            return Ok(format!(
+                "OK stint={} time={} laps={} fuel={} per_lap={} laps_left={} time_left={} window={}",
+                stint.number(),
+                format_stint_time(stint.time(now)),
+                stint.laps(),
+                fuel(stint.fuel_l(), 1),
+                fuel(stint.fuel_per_lap(), 2),
+                fuel(stint.laps_remaining(), 1),
+                stint.time_remaining().map_or("-".to_string(), format_stint_time),
+                if stint.pit_window_open() { "open" } else { "closed" }
+            ));
+        }
+        ["stint", "new"] => {
+            let now = state.now();
+            state.stint.new_stint(now);
+        }
+        ["tyres"] => {
+            let esp32 = &state.latest_esp32_data;
+            let value = |value: Option<f32>, decimals: usize| value.map_or("-".to_string(), |v| format!("{:.*}", decimals, v));
+            let corners: Vec<String> = CORNERS
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("{}={}/{}", name, value(esp32.tyre_pressure_bar(i), 2), value(esp32.tyre_temp_c(i), 1)))
+                .collect();
+            let leaks: Vec<&str> = CORNERS.iter().enumerate().filter(|(i, _)| state.tyres.leaking(*i)).map(|(_, name)| *name).collect();
+            return Ok(format!(
+                "OK {} leaks={}",
+                corners.join(" "),
+                if leaks.is_empty() { "-".to_string() } else { leaks.join(",") }
+            ));
+        }
+        ["tyres", "reset"] => {
+            let now = state.now();
+            state.tyres.reset(now);
+        }
+        ["tracks"] => {
+            let names: Vec<String> = state.tracks.tracks.iter().map(|t| format!("\"{}\"", t.name)).collect();
+            return Ok(format!("OK {}", names.join(" ")).trim_end().to_string());
+        }
+        ["setup", "clear"] => state.set_car_setup(None),
+        ["setup", name @ ..] if !name.is_empty() => state.set_car_setup(Some(name.join(" "))),
+        ["conditions", "clear"] => state.lap_history.conditions = None,
+        ["conditions", text @ ..] if !text.is_empty() => state.lap_history.conditions = Some(text.join(" ")),
+        ["pb", track @ ..] => {
+            let track = match track {
+                [] => state.active_track.as_ref().map(|t| t.name.clone()).ok_or("no active track")?,
+                name => name.join(" "),
+            };
+            let bests: Vec<String> = state
+                .lap_history
+                .personal_bests(&track)
+                .into_iter()
+                .map(|(setup, lap)| {
+                    format!(
+                        "{}={} ({:.0} km/h)",
+                        setup.as_deref().unwrap_or("default"),
+                        format_lap_time(lap.lap_time()),
+                        lap.max_speed_kph
+                    )
+                })
+                .collect();
+            return Ok(format!("OK \"{}\" {}", track, bests.join("; ")).trim_end().to_string());
+        }
+        ["history"] => return Ok(format_history(state, 5)),
+        ["history", count] => {
+            let count: usize = count.parse().map_err(|_| format!("invalid count: {}", count))?;
+            return Ok(format_history(state, count));
+        }
+        ["delta", args @ ..] => return execute_delta(state, args),
+        ["accel", args @ ..] => return execute_accel(state, args),
+        ["braking", args @ ..] => return execute_braking(state, args),
+        ["speed", args @ ..] => return execute_speed(state, args),
+        ["gmeter", args @ ..] => return execute_gmeter(state, args),
+        ["trace"] => {
+            return Ok(format!(
+                "OK span={:.0}s {}",
+                state.settings.trace_seconds,
+                if state.inputs.is_frozen() { "frozen" } else { "live" }
+            ));
+        }
+        ["trace", "freeze"] => {
+            // One button both freezes and releases the trace
+            if state.inputs.is_frozen() {
+                state.inputs.resume();
+            } else {
+                let now = state.now();
+                state.inputs.freeze(now);
+            }
+        }
+        ["trace", "span", seconds] => {
+            let seconds: f32 = seconds.parse().map_err(|_| format!("invalid span: {}", seconds))?;
+            if !Settings::valid_trace_seconds(seconds) {
+                return Err(format!("span must be 1 to {} s", HISTORY_SPAN.as_secs()));
+            }
+            state.update_settings(|settings| settings.trace_seconds = seconds);
+        }
+        ["map"] => {
+            let on_off = |on: bool| if on { "on" } else { "off" };
+            return Ok(format!(
+                "OK outline={} points speed={} ghost={}",
+                state.map_outline().len(),
+                on_off(state.settings.map_speed_colors),
+                on_off(state.settings.map_ghost)
+            ));
+        }
+        ["map", "speed", setting @ ("on" | "off")] => {
+            let map_speed_colors = *setting == "on";
+            state.update_settings(|settings| settings.map_speed_colors = map_speed_colors);
+        }
+        ["map", "ghost", setting @ ("on" | "off")] => {
+            let map_ghost = *setting == "on";
+            state.update_settings(|settings| settings.map_ghost = map_ghost);
+        }
+        ["replay", args @ ..] => return execute_replay(state, args),
+        ["log_level", namespace, "reset"] => logging::reset_namespace_level(logging::resolve_namespace(namespace)),
+        ["log_level", namespace, level] => {
+            let level: LevelFilter = level.parse().map_err(|_| format!("invalid level: {}", level))?;
+            logging::set_namespace_level(logging::resolve_namespace(namespace), level);
+        }
+        ["log_levels"] => {
+            let levels: Vec<String> = logging::namespace_levels()
+                .into_iter()
+                .map(|(namespace, level)| format!("{}={}", namespace, level))
+                .collect();
+            return Ok(format!("OK {}", levels.join(" ")).trim_end().to_string());
+        }
+        _ => return Err("unknown command".to_string()),
+    }
+    Ok("OK".to_string())
+}
+
+fn execute_delta(state: &mut TelemetryState, args: &[&str]) -> Result<String, String> {
+    match args {
+        [] => {
+            let delta = &state.lap_delta;
+            let reference = match (delta.source(), delta.reference()) {
+                (ReferenceSource::SessionBest, Some(r)) => format!("session_best({})", format_lap_time(r.lap_time())),
+                (ReferenceSource::Loaded, Some(r)) => format!("loaded({})", format_lap_time(r.lap_time())),
+                (ReferenceSource::AllTimeBest, Some(r)) => format!("all_time_best({})", format_lap_time(r.lap_time())),
+                (_, None) => "none".to_string(),
+            };
+            return Ok(format!(
+                "OK delta={} predicted={} trend={} reference={}",
+                delta.delta_s().map(format_delta).unwrap_or_else(|| "-".to_string()),
+                delta.predicted_lap_time().map(format_lap_time).unwrap_or_else(|| "-".to_string()),
+                delta.trend().map(format_delta).unwrap_or_else(|| "-".to_string()),
+                reference,
+            ));
+        }
+        ["reference", "session_best"] => state.lap_delta.use_session_best(state.lap_timer.as_ref()),
+        ["reference", "all_time_best"] => state.use_all_time_best(),
+        ["load", path] => {
+            let reference = ReferenceLap::load(path).map_err(|e| e.to_string())?;
+            state.lap_delta.use_reference(reference);
+        }
+        ["save", path] => {
+            let best = state.lap_timer.as_ref().and_then(|t| t.best_lap()).ok_or("no lap completed")?;
+            ReferenceLap::from_lap(best).save(path).map_err(|e| e.to_string())?;
+        }
+        _ => return Err("unknown delta command".to_string()),
+    }
+    Ok("OK".to_string())
+}
+
+fn execute_accel(state: &mut TelemetryState, args: &[&str]) -> Result<String, String> {
+    match args {
+        ["on"] => state.set_accel_timing(true),
+        ["off"] => state.set_accel_timing(false),
+        [] => {
+            let timer = state.accel_timer.as_ref().ok_or("acceleration timing off")?;
+            let last = match timer.last_result() {
+                Some(r) => format!("\"{}\" {:.2}s", r.name, r.time_s),
+                None => "-".to_string(),
+            };
+            let rejected = match timer.last_rejection() {
+                Some((name, reason)) => format!("\"{}\" ({})", name, reason),
+                None => "-".to_string(),
+            };
+            return Ok(format!(
+                "OK status={} rollout={} last={} rejected={}",
+                timer.status(),
+                if timer.rollout() { "on" } else { "off" },
+                last,
+                rejected
+            ));
+        }
+        ["results"] => {
+            let timer = state.accel_timer.as_ref().ok_or("acceleration timing off")?;
+            let results: Vec<String> = timer
+                .runs()
+                .iter()
+                .filter_map(|run| timer.best(&run.name))
+                .map(|r| format!("\"{}\" {:.2}s {:.1}m {:.0}km/h", r.name, r.time_s, r.distance_m, r.end_speed_kph))
+                .collect();
+            return Ok(format!("OK {}", results.join("; ")).trim_end().to_string());
+        }
+        ["rollout", setting @ ("on" | "off")] => {
+            let timer = state.accel_timer.as_mut().ok_or("acceleration timing off")?;
+            timer.set_rollout(*setting == "on");
+        }
+        _ => return Err("unknown accel command".to_string()),
+    }
+    Ok("OK".to_string())
+}
+
+fn execute_gmeter(state: &mut TelemetryState, args: &[&str]) -> Result<String, String> {
+    match args {
+        [] => {
+            let [front, right, rear, left] = state.g_force.peaks();
+            let settings = &state.settings;
+            return Ok(format!(
+                "OK peak front={:.2} right={:.2} rear={:.2} left={:.2} friction={} limit={:.2}",
+                front,
+                right,
+                rear,
+                left,
+                if settings.friction_circle { "on" } else { "off" },
+                settings.friction_limit_g
+            ));
+        }
+        ["reset"] => state.g_force.reset(),
+        ["friction", setting @ ("on" | "off")] => {
+            let friction_circle = *setting == "on";
+            state.update_settings(|settings| settings.friction_circle = friction_circle);
+        }
+        ["limit", g] => {
+            let limit: f32 = g.parse().map_err(|_| format!("invalid limit: {}", g))?;
+            if !Settings::valid_friction_limit_g(limit) {
+                return Err(format!("limit must be above 0 and at most {} G", MAX_FRICTION_LIMIT_G));
+            }
+            state.update_settings(|settings| settings.friction_limit_g = limit);
+        }
+        _ => return Err("unknown gmeter command".to_string()),
+    }
+    Ok("OK".to_string())
+}
+
+fn execute_speed(state: &mut TelemetryState, args: &[&str]) -> Result<String, String> {
+    match args {
+        [] => {
+            let settings = &state.settings;
+            let reading = state.speed();
+            let speed = match reading {
+                Some(reading) => format!("{:.1}", settings.speed_unit.convert_kph(reading.kph)),
+                None => "-".to_string(),
+            };
+            let using = match reading {
+                Some(reading) => match reading.gnss_quality {
+                    Some(quality) => format!("{} ({:?})", reading.source, quality),
+                    None => reading.source.to_string(),
+                },
+                None => "-".to_string(),
+            };
+            return Ok(format!(
+                "OK speed={} unit={} source={} using={} arc={}",
+                speed,
+                settings.speed_unit.label(),
+                settings.speed_source,
+                using,
+                if settings.speed_arc { "on" } else { "off" }
+            ));
+        }
+        ["unit", unit] => {
+            let unit = match *unit {
+                "kph" => SpeedUnit::Kph,
+                "mph" => SpeedUnit::Mph,
+                _ => return Err(format!("invalid unit: {}", unit)),
+            };
+            state.update_settings(|settings| settings.speed_unit = unit);
+        }
+        ["source", source] => {
+            let source = match *source {
+                "wheel" => SpeedSource::Wheel,
+                "gnss" => SpeedSource::Gnss,
+                "fused" => SpeedSource::Fused,
+                _ => return Err(format!("invalid speed source: {}", source)),
+            };
+            state.update_settings(|settings| settings.speed_source = source);
+        }
+        ["arc", setting @ ("on" | "off")] => {
+            let arc = *setting == "on";
+            state.update_settings(|settings| settings.speed_arc = arc);
+        }
+        _ => return Err("unknown speed command".to_string()),
+    }
+    Ok("OK".to_string())
+}
+
+fn execute_braking(state: &mut TelemetryState, args: &[&str]) -> Result<String, String> {
+    let bar = |v: Option<f32>| v.map(|v| format!("{:.1}bar", v)).unwrap_or_else(|| "-".to_string());
+    match args {
+        ["on"] => state.set_braking_tests(true),
+        ["off"] => state.set_braking_tests(false),
+        [] => {
+            let timer = state.braking_timer.as_ref().ok_or("braking tests off")?;
+            let last = match timer.last_result() {
+                Some(r) => format!(
+                    "\"{}\" {:.1}m {:.2}s peak={:.2}g mean={:.2}g brake={}/{} slope={:+.1}% level={:.1}m/{:.2}s",
+                    r.name,
+                    r.distance_m,
+                    r.time_s,
+                    r.peak_decel_g,
+                    r.mean_decel_g,
+                    bar(r.peak_brake_bar),
+                    bar(r.mean_brake_bar),
+                    r.slope_percent,
+                    r.corrected_distance_m,
+                    r.corrected_time_s
+                ),
+                None => "-".to_string(),
+            };
+            let rejected = match timer.last_rejection() {
+                Some((name, reason)) => format!("\"{}\" ({})", name, reason),
+                None => "-".to_string(),
+            };
+            return Ok(format!("OK status={} last={} rejected={}", timer.status(), last, rejected));
+        }
+        ["results"] => {
+            let timer = state.braking_timer.as_ref().ok_or("braking tests off")?;
+            let results: Vec<String> = timer
+                .tests()
+                .iter()
+                .filter_map(|test| timer.best(&test.name))
+                .map(|r| format!("\"{}\" {:.1}m {:.2}s (level {:.1}m)", r.name, r.distance_m, r.time_s, r.corrected_distance_m))
+                .collect();
+            return Ok(format!("OK {}", results.join("; ")).trim_end().to_string());
+        }
+        _ => return Err("unknown braking command".to_string()),
+    }
+    Ok("OK".to_string())
+}
+
+fn format_history(state: &TelemetryState, count: usize) -> String {
+    let sessions: Vec<String> = state
+        .lap_history
+        .recent_sessions(count)
+        .into_iter()
+        .map(|s| {
+            let (year, month, day, hour, minute, _) = crate::export::utc_from_unix_ms(s.session_unix_ms);
+            format!(
+                "\"{}\" {:04}-{:02}-{:02}T{:02}:{:02}Z laps={} best={}",
+                s.track,
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                s.laps,
+                s.best_lap.map(format_lap_time).unwrap_or_else(|| "-".to_string())
+            )
+        })
+        .collect();
+    format!("OK {}", sessions.join("; ")).trim_end().to_string()
+}
+
+fn format_sectors(times: &[Option<std::time::Duration>]) -> String {
+    times
+        .iter()
+        .map(|t| t.map(format_lap_time).unwrap_or_else(|| "-".to_string()))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `LAT1 LON1 LAT2 LON2 [HEADING]`
+fn parse_timing_line(coords: &[&str]) -> Result<TimingLine, String> {
+    if !(coords.len() == 4 || coords.len() == 5) {
+        return Err("expected LAT1 LON1 LAT2 LON2 [HEADING]".to_string());
+    }
+    let values = coords
+        .iter()
+        .map(|v| v.parse::<f64>().map_err(|_| format!("invalid number: {}", v)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(TimingLine {
+        a: GeoPoint::new(values[0], values[1]),
+        b: GeoPoint::new(values[2], values[3]),
+        heading_deg: values.get(4).map(|h| *h as f32),
+    })
+}
+
+fn start_recording(state: &mut TelemetryState, path: std::path::PathBuf) -> Result<String, String> {
+    state.start_recording(&path).map_err(|e| e.to_string())?;
+    Ok(format!("OK {}", path.display()))
+}
+
+fn execute_replay(state: &mut TelemetryState, args: &[&str]) -> Result<String, String> {
+    let control = state.replay.as_mut().ok_or("no replay running")?;
+    match args {
+        ["pause"] => control.paused = true,
+        ["resume"] => control.paused = false,
+        ["speed", speed] => {
+            let speed: f32 = speed.parse().map_err(|_| format!("invalid speed: {}", speed))?;
+            if !valid_replay_speed(speed) {
+                return Err(format!("speed must be above 0 and at most {}", MAX_REPLAY_SPEED));
+            }
+            control.speed = speed;
+        }
+        ["seek", seconds] => {
+            let position: f32 = seconds.parse().map_err(|_| format!("invalid position: {}", seconds))?;
+            let position = Duration::try_from_secs_f32(position.max(0.0))
+                .map_err(|_| format!("invalid position: {}", seconds))?;
+            control.seek_to = Some(position.min(control.duration));
+        }
+        ["status"] => {
+            return Ok(format!(
+                "OK file={} recorded_unix_ms={} position={:.2} duration={:.2} speed={} paused={} finished={}",
+                control.path.display(),
+                control.recorded_unix_ms,
+                control.position.as_secs_f32(),
+                control.duration.as_secs_f32(),
+                control.speed,
+                control.paused,
+                control.finished,
+            ));
+        }
+        ["stop"] => state.replay = None,
+        _ => return Err("unknown replay command".to_string()),
+    }
+    Ok("OK".to_string())
+}
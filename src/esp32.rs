@@ -55,21 +55,16 @@ impl ESP32Connection {
                         
                         // Check for frame end (0x55)
                         if byte[0] == 0x55 && frame_buffer.len() >= 8 {
-                            if let Ok(data) = Self::parse_frame(&frame_buffer) {
-                                let state = telemetry_state.clone();
+                            let mut state = telemetry_state.lock().await;
+                            if let Ok(data) = state.ingest_esp32_frame(&frame_buffer) {
                                 let update_time = Instant::now();
-                                
+
                                 // Update last successful update time and last_values
                                 last_successful_update = update_time;
                                 connection.last_update = update_time;
-                                connection.last_values = data.clone();
-                                
-                                tokio::spawn(async move {
-                                    let mut state = state.lock().await;
-                                    state.latest_esp32_data = data;
-                                    //debug!(target: ESP32_NAMESPACE, "Updated ESP32 data successfully");
-                                });
+                                connection.last_values = data;
                             }
+                            drop(state);
                             frame_buffer.clear();
                         }
                     }
@@ -98,7 +93,7 @@ impl ESP32Connection {
         }
     }
 
-    fn parse_frame(frame: &[u8]) -> Result<ESP32Data, Box<dyn Error + Send + Sync>> {
+    pub fn parse_frame(frame: &[u8]) -> Result<ESP32Data, Box<dyn Error + Send + Sync>> {
         if frame.len() < 8 { // Minimum: HDR, LEN, VER, CRC16, EOF
             return Err("Frame too short".into());
         }
@@ -159,6 +154,8 @@ impl ESP32Connection {
                     let idx = (id - 0x0F) as usize;
                     data.tyre_temps[idx] = Some(i16::from_be_bytes([frame[pos], frame[pos + 1]]));
                 }
+                0x13 => data.coolant_temp = Some(i16::from_be_bytes([frame[pos], frame[pos + 1]])),
+                0x14 => data.oil_temp = Some(i16::from_be_bytes([frame[pos], frame[pos + 1]])),
                 _ => {}
             }
             pos += len;
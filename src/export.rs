@@ -0,0 +1,192 @@
+pub mod csv;
+pub mod gpx;
+pub mod kml;
+pub mod motec;
+
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::cli::ExportFormat;
+use crate::session::file::SessionHeader;
+use crate::session::replay::Replayer;
+use crate::racebox::parser::RaceBoxData;
+use crate::session::{RecordSource, SessionError};
+use crate::telemetry::TelemetryState;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Failed to read session: {0}")]
+    Session(#[from] SessionError),
+
+    #[error("Failed to write export: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid sample rate: {0}")]
+    InvalidRate(f32),
+}
+
+/// Run an export requested from the command line
+pub fn run(format: ExportFormat, session: &Path, output: &Path, rate_hz: f32) -> Result<(), ExportError> {
+    match format {
+        ExportFormat::Csv => csv::write_csv(&resample(session, rate_hz)?, output),
+        ExportFormat::Motec => motec::write_ld(&resample(session, rate_hz)?, output),
+        ExportFormat::Gpx => gpx::write_gpx(&gnss_track(session)?, output),
+        ExportFormat::Kml => kml::write_kml(&gnss_track(session)?, output),
+    }
+}
+
+/// A logged channel, as it appears in exported files
+pub struct Channel {
+    pub name: &'static str,
+    /// Short name, limited to 8 characters by the MoTeC format
+    pub short_name: &'static str,
+    pub unit: &'static str,
+    pub decimals: usize,
+    pub read: fn(&TelemetryState) -> Option<f64>,
+}
+
+/// Every channel we export, in column order
+pub const CHANNELS: &[Channel] = &[
+    Channel { name: "GPS Speed", short_name: "GPSSpd", unit: "km/h", decimals: 2, read: |s| s.latest_racebox_data.as_ref().map(|d| d.speed_kph as f64) },
+    Channel { name: "GPS Latitude", short_name: "GPSLat", unit: "deg", decimals: 7, read: |s| s.latest_racebox_data.as_ref().map(|d| d.latitude) },
+    Channel { name: "GPS Longitude", short_name: "GPSLon", unit: "deg", decimals: 7, read: |s| s.latest_racebox_data.as_ref().map(|d| d.longitude) },
+    Channel { name: "GPS Altitude", short_name: "GPSAlt", unit: "m", decimals: 1, read: |s| s.latest_racebox_data.as_ref().map(|d| d.msl_alt) },
+    Channel { name: "GPS Heading", short_name: "GPSHead", unit: "deg", decimals: 1, read: |s| s.latest_racebox_data.as_ref().map(|d| d.heading_deg as f64) },
+    Channel { name: "GPS Sats", short_name: "GPSSats", unit: "", decimals: 0, read: |s| s.latest_racebox_data.as_ref().map(|d| d.num_sv as f64) },
+    Channel { name: "G Force Lat", short_name: "GLat", unit: "G", decimals: 3, read: |s| s.latest_racebox_data.as_ref().map(|d| d.g_force_x as f64) },
+    Channel { name: "G Force Long", short_name: "GLong", unit: "G", decimals: 3, read: |s| s.latest_racebox_data.as_ref().map(|d| d.g_force_y as f64) },
+    Channel { name: "G Force Vert", short_name: "GVert", unit: "G", decimals: 3, read: |s| s.latest_racebox_data.as_ref().map(|d| d.g_force_z as f64) },
+    Channel { name: "Yaw Rate", short_name: "YawRate", unit: "deg/s", decimals: 2, read: |s| s.latest_racebox_data.as_ref().map(|d| d.rot_rate_z as f64) },
+    Channel { name: "Engine RPM", short_name: "RPM", unit: "rpm", decimals: 0, read: |s| s.latest_esp32_data.rpm.map(|v| v as f64) },
+    Channel { name: "Wheel Speed", short_name: "WhlSpd", unit: "km/h", decimals: 0, read: |s| s.latest_esp32_data.speed.map(|v| v as f64) },
+    Channel { name: "Gear", short_name: "Gear", unit: "", decimals: 0, read: |s| s.latest_esp32_data.gear_position.map(|v| v as f64) },
+    Channel { name: "Throttle Pos", short_name: "TPS", unit: "%", decimals: 0, read: |s| s.latest_esp32_data.throttle_position.map(|v| v as f64) },
+    Channel { name: "Brake Pressure", short_name: "BrkPres", unit: "bar", decimals: 2, read: |s| s.latest_esp32_data.brake_pressure_bar().map(|v| v as f64) },
+    Channel { name: "Steering Angle", short_name: "Steer", unit: "deg", decimals: 1, read: |s| s.latest_esp32_data.steering_angle_deg().map(|v| v as f64) },
+    Channel { name: "Boost Pressure", short_name: "Boost", unit: "bar", decimals: 3, read: |s| s.latest_esp32_data.boost_bar().map(|v| v as f64) },
+    Channel { name: "Oil Pressure Raw", short_name: "OilPRaw", unit: "", decimals: 0, read: |s| s.latest_esp32_data.oil_pressure.map(|v| v as f64) },
+    Channel { name: "Fuel Level Raw", short_name: "FuelRaw", unit: "", decimals: 0, read: |s| s.latest_esp32_data.fuel_level.map(|v| v as f64) },
+    Channel { name: "Fuel Level", short_name: "Fuel", unit: "l", decimals: 2, read: |s| s.fuel_litres().map(|v| v as f64) },
+    Channel { name: "Tyre Pres FL", short_name: "TPresFL", unit: "bar", decimals: 2, read: |s| s.latest_esp32_data.tyre_pressure_bar(0).map(|v| v as f64) },
+    Channel { name: "Tyre Pres FR", short_name: "TPresFR", unit: "bar", decimals: 2, read: |s| s.latest_esp32_data.tyre_pressure_bar(1).map(|v| v as f64) },
+    Channel { name: "Tyre Pres RL", short_name: "TPresRL", unit: "bar", decimals: 2, read: |s| s.latest_esp32_data.tyre_pressure_bar(2).map(|v| v as f64) },
+    Channel { name: "Tyre Pres RR", short_name: "TPresRR", unit: "bar", decimals: 2, read: |s| s.latest_esp32_data.tyre_pressure_bar(3).map(|v| v as f64) },
+    Channel { name: "Tyre Temp FL", short_name: "TTempFL", unit: "C", decimals: 1, read: |s| s.latest_esp32_data.tyre_temp_c(0).map(|v| v as f64) },
+    Channel { name: "Tyre Temp FR", short_name: "TTempFR", unit: "C", decimals: 1, read: |s| s.latest_esp32_data.tyre_temp_c(1).map(|v| v as f64) },
+    Channel { name: "Tyre Temp RL", short_name: "TTempRL", unit: "C", decimals: 1, read: |s| s.latest_esp32_data.tyre_temp_c(2).map(|v| v as f64) },
+    Channel { name: "Tyre Temp RR", short_name: "TTempRR", unit: "C", decimals: 1, read: |s| s.latest_esp32_data.tyre_temp_c(3).map(|v| v as f64) },
+    Channel { name: "Coolant Temp", short_name: "CoolTemp", unit: "C", decimals: 1, read: |s| s.latest_esp32_data.coolant_temp_c().map(|v| v as f64) },
+    Channel { name: "Oil Temp", short_name: "OilTemp", unit: "C", decimals: 1, read: |s| s.latest_esp32_data.oil_temp_c().map(|v| v as f64) },
+];
+
+/// A session replayed headlessly and resampled at a fixed rate
+pub struct ResampledSession {
+    pub header: SessionHeader,
+    pub rate_hz: f32,
+    /// One row per sample, one value per entry of `CHANNELS`
+    pub rows: Vec<Vec<Option<f64>>>,
+    /// Lap (beacon) markers, as offsets from the start of the session
+    pub lap_markers: Vec<Duration>,
+}
+
+impl ResampledSession {
+    pub fn sample_time(&self, index: usize) -> f64 {
+        index as f64 / self.rate_hz as f64
+    }
+
+    /// Lap number at `time`: 0 before the first marker, then 1, 2...
+    pub fn lap_at(&self, time: f64) -> usize {
+        self.lap_markers.iter().filter(|m| m.as_secs_f64() <= time).count()
+    }
+}
+
+/// Replay a session through a fresh `TelemetryState` and sample every channel at `rate_hz`.
+///
+/// Values are sample-and-hold: each row carries the latest value received at
+/// or before that instant, or `None` if the channel has not been seen yet.
+pub fn resample<P: AsRef<Path>>(path: P, rate_hz: f32) -> Result<ResampledSession, ExportError> {
+    if !(rate_hz.is_finite() && rate_hz > 0.0) {
+        return Err(ExportError::InvalidRate(rate_hz));
+    }
+    let mut replayer = Replayer::open(path)?;
+    let mut state = TelemetryState::new();
+    state.set_replay_time(Duration::ZERO);
+
+    let sample_count = (replayer.duration().as_secs_f64() * rate_hz as f64).floor() as usize + 1;
+    let mut rows = Vec::with_capacity(sample_count);
+    for i in 0..sample_count {
+        let time = Duration::from_secs_f64(i as f64 / rate_hz as f64);
+        replayer.advance_to(&mut state, time);
+        rows.push(CHANNELS.iter().map(|c| (c.read)(&state)).collect());
+    }
+    replayer.advance_to(&mut state, Duration::MAX);
+
+    Ok(ResampledSession {
+        header: replayer.header(),
+        rate_hz,
+        rows,
+        lap_markers: state.lap_markers(),
+    })
+}
+
+/// Replay a session and collect every RaceBox sample that has a valid fix
+pub fn gnss_track<P: AsRef<Path>>(path: P) -> Result<Vec<RaceBoxData>, ExportError> {
+    let mut replayer = Replayer::open(path)?;
+    let mut state = TelemetryState::new();
+    let mut track = Vec::new();
+    while let Some(source) = replayer.step(&mut state) {
+        if source != RecordSource::RaceBox {
+            continue;
+        }
+        if let Some(data) = &state.latest_racebox_data {
+            // Skip repeats: a packet that failed to parse leaves the previous sample in place
+            let repeated = track.last().is_some_and(|last: &RaceBoxData| last.timestamp_ms == data.timestamp_ms);
+            if data.fix_ok && !repeated {
+                track.push(data.clone());
+            }
+        }
+    }
+    Ok(track)
+}
+
+/// ISO 8601 UTC timestamp of a RaceBox sample, with milliseconds
+pub fn iso_timestamp(data: &RaceBoxData) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        data.year,
+        data.month,
+        data.day,
+        data.hour,
+        data.minute,
+        data.second,
+        data.timestamp_ms % 1000
+    )
+}
+
+/// Break a Unix timestamp into UTC (year, month, day, hour, minute, second)
+pub fn utc_from_unix_ms(unix_ms: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let secs = (unix_ms / 1000) as i64;
+    let days = secs.div_euclid(86_400);
+    let time_of_day = secs.rem_euclid(86_400);
+
+    // Civil-from-days, after Howard Hinnant's date algorithms
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (
+        year,
+        month,
+        day,
+        (time_of_day / 3600) as u32,
+        (time_of_day % 3600 / 60) as u32,
+        (time_of_day % 60) as u32,
+    )
+}
@@ -0,0 +1,38 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::export::{ExportError, ResampledSession, CHANNELS};
+
+/// Write a resampled session as CSV.
+///
+/// The first column is the session time in seconds, the second the lap number,
+/// then one column per channel headed `Name (unit)`. Missing values are left empty.
+pub fn write_csv<P: AsRef<Path>>(session: &ResampledSession, path: P) -> Result<(), ExportError> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    write!(out, "Time (s),Lap")?;
+    for channel in CHANNELS {
+        if channel.unit.is_empty() {
+            write!(out, ",{}", channel.name)?;
+        } else {
+            write!(out, ",{} ({})", channel.name, channel.unit)?;
+        }
+    }
+    writeln!(out)?;
+
+    for (i, row) in session.rows.iter().enumerate() {
+        let time = session.sample_time(i);
+        write!(out, "{:.3},{}", time, session.lap_at(time))?;
+        for (channel, value) in CHANNELS.iter().zip(row) {
+            match value {
+                Some(v) => write!(out, ",{:.*}", channel.decimals, v)?,
+                None => write!(out, ",")?,
+            }
+        }
+        writeln!(out)?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::export::{iso_timestamp, ExportError};
+use crate::racebox::parser::RaceBoxData;
+
+/// Write a GNSS track as GPX 1.1.
+///
+/// Speed (m/s) and course (degrees) are stored per point using the Garmin
+/// TrackPointExtension schema, which most mapping tools understand.
+pub fn write_gpx<P: AsRef<Path>>(track: &[RaceBoxData], path: P) -> Result<(), ExportError> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<gpx version="1.1" creator="vx220-dashboard" xmlns="http://www.topografix.com/GPX/1/1" xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v2">"#
+    )?;
+    if let Some(first) = track.first() {
+        writeln!(out, "  <metadata><time>{}</time></metadata>", iso_timestamp(first))?;
+    }
+    writeln!(out, "  <trk>")?;
+    writeln!(out, "    <name>VX220 session</name>")?;
+    writeln!(out, "    <trkseg>")?;
+    for point in track {
+        writeln!(out, r#"      <trkpt lat="{:.7}" lon="{:.7}">"#, point.latitude, point.longitude)?;
+        writeln!(out, "        <ele>{:.2}</ele>", point.msl_alt)?;
+        writeln!(out, "        <time>{}</time>", iso_timestamp(point))?;
+        writeln!(out, "        <sat>{}</sat>", point.num_sv)?;
+        writeln!(out, "        <pdop>{:.2}</pdop>", point.pdop)?;
+        writeln!(out, "        <extensions>")?;
+        writeln!(out, "          <gpxtpx:TrackPointExtension>")?;
+        writeln!(out, "            <gpxtpx:speed>{:.3}</gpxtpx:speed>", point.speed_kph / 3.6)?;
+        writeln!(out, "            <gpxtpx:course>{:.2}</gpxtpx:course>", point.heading_deg)?;
+        writeln!(out, "          </gpxtpx:TrackPointExtension>")?;
+        writeln!(out, "        </extensions>")?;
+        writeln!(out, "      </trkpt>")?;
+    }
+    writeln!(out, "    </trkseg>")?;
+    writeln!(out, "  </trk>")?;
+    writeln!(out, "</gpx>")?;
+
+    out.flush()?;
+    Ok(())
+}
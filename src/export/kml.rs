@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::export::ExportError;
+use crate::racebox::parser::RaceBoxData;
+
+/// Number of speed bands the track is split into
+const SPEED_BANDS: usize = 8;
+const LINE_WIDTH: f32 = 4.0;
+
+/// KML `aabbggrr` colour for a band, running blue (slow) → green → yellow → red (fast)
+fn band_color(band: usize) -> String {
+    let t = band as f32 / (SPEED_BANDS - 1) as f32;
+    let (r, g, b) = if t < 0.5 {
+        let k = t * 2.0;
+        (0.0, k, 1.0 - k)
+    } else {
+        let k = (t - 0.5) * 2.0;
+        (k, 1.0 - k * 0.5, 0.0)
+    };
+    format!("ff{:02x}{:02x}{:02x}", (b * 255.0) as u8, (g * 255.0) as u8, (r * 255.0) as u8)
+}
+
+/// Write a GNSS track as KML, colour-coded by speed.
+///
+/// The track is cut into runs of consecutive points falling in the same speed
+/// band, each drawn as its own line with the band's style. Runs share their
+/// boundary point so the line stays continuous.
+pub fn write_kml<P: AsRef<Path>>(track: &[RaceBoxData], path: P) -> Result<(), ExportError> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    let min_speed = track.iter().map(|p| p.speed_kph).fold(f32::INFINITY, f32::min);
+    let max_speed = track.iter().map(|p| p.speed_kph).fold(f32::NEG_INFINITY, f32::max);
+    let band_width = ((max_speed - min_speed) / SPEED_BANDS as f32).max(f32::EPSILON);
+    let band_of = |p: &RaceBoxData| (((p.speed_kph - min_speed) / band_width) as usize).min(SPEED_BANDS - 1);
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(out, "<Document>")?;
+    writeln!(out, "  <name>VX220 session</name>")?;
+    for band in 0..SPEED_BANDS {
+        writeln!(out, r#"  <Style id="speed{}">"#, band)?;
+        writeln!(out, "    <LineStyle><color>{}</color><width>{}</width></LineStyle>", band_color(band), LINE_WIDTH)?;
+        writeln!(out, "  </Style>")?;
+    }
+
+    let mut start = 0;
+    while start + 1 < track.len() {
+        let band = band_of(&track[start + 1]);
+        let mut end = start + 1;
+        while end + 1 < track.len() && band_of(&track[end + 1]) == band {
+            end += 1;
+        }
+
+        let band_low = min_speed + band as f32 * band_width;
+        writeln!(out, "  <Placemark>")?;
+        writeln!(out, "    <name>{:.0}–{:.0} km/h</name>", band_low, band_low + band_width)?;
+        writeln!(out, "    <styleUrl>#speed{}</styleUrl>", band)?;
+        writeln!(out, "    <LineString>")?;
+        writeln!(out, "      <altitudeMode>clampToGround</altitudeMode>")?;
+        write!(out, "      <coordinates>")?;
+        for point in &track[start..=end] {
+            write!(out, "{:.7},{:.7},{:.1} ", point.longitude, point.latitude, point.msl_alt)?;
+        }
+        writeln!(out, "</coordinates>")?;
+        writeln!(out, "    </LineString>")?;
+        writeln!(out, "  </Placemark>")?;
+
+        start = end;
+    }
+
+    writeln!(out, "</Document>")?;
+    writeln!(out, "</kml>")?;
+
+    out.flush()?;
+    Ok(())
+}
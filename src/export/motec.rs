@@ -0,0 +1,190 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::export::{utc_from_unix_ms, ExportError, ResampledSession, CHANNELS};
+
+// MoTeC i2 `.ld` layout, as documented by the community (e.g. the `ldparser`
+// project). Everything is little-endian and fixed size:
+//
+// | HEADER (1762) | EVENT (1154) | VENUE (1100) | VEHICLE (260) | CHANNEL META (124 each) | DATA |
+//
+// Channel data is stored as f32 with shift 0, multiplier 1, scale 1 and 0 decimal
+// shift, so i2 reads the values unchanged. Lap markers go in the `.ldx` sidecar.
+const HEADER_SIZE: usize = 1762;
+const EVENT_SIZE: usize = 1154;
+const VENUE_SIZE: usize = 1100;
+const VEHICLE_SIZE: usize = 260;
+const CHANNEL_META_SIZE: usize = 124;
+
+const EVENT_PTR: usize = HEADER_SIZE;
+const VENUE_PTR: usize = EVENT_PTR + EVENT_SIZE;
+const VEHICLE_PTR: usize = VENUE_PTR + VENUE_SIZE;
+const CHANNEL_META_PTR: usize = VEHICLE_PTR + VEHICLE_SIZE;
+
+const DTYPE_FLOAT: u16 = 0x07;
+const DTYPE_FLOAT_SIZE: u16 = 4;
+
+const VEHICLE_ID: &str = "VX220 Turbo";
+
+/// Little-endian byte buffer with fixed-width string fields
+struct LdBuffer(Vec<u8>);
+
+impl LdBuffer {
+    fn u16(&mut self, v: u16) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn i16(&mut self, v: i16) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn pad(&mut self, n: usize) {
+        self.0.resize(self.0.len() + n, 0);
+    }
+
+    /// Write `s` into a zero-padded field of `n` bytes, truncating if needed
+    fn str(&mut self, s: &str, n: usize) {
+        let bytes = s.as_bytes();
+        let len = bytes.len().min(n);
+        self.0.extend_from_slice(&bytes[..len]);
+        self.pad(n - len);
+    }
+}
+
+/// Write a resampled session as a MoTeC `.ld` file, plus an `.ldx` file with lap markers
+pub fn write_ld<P: AsRef<Path>>(session: &ResampledSession, path: P) -> Result<(), ExportError> {
+    let path = path.as_ref();
+    let sample_count = session.rows.len();
+    let data_ptr = CHANNEL_META_PTR + CHANNELS.len() * CHANNEL_META_SIZE;
+    let (year, month, day, hour, minute, second) = utc_from_unix_ms(session.header.started_unix_ms);
+
+    let mut buf = LdBuffer(Vec::with_capacity(data_ptr + CHANNELS.len() * sample_count * 4));
+
+    // Header
+    buf.u32(0x40);
+    buf.pad(4);
+    buf.u32(CHANNEL_META_PTR as u32);
+    buf.u32(data_ptr as u32);
+    buf.pad(20);
+    buf.u32(EVENT_PTR as u32);
+    buf.pad(24);
+    buf.u16(1);
+    buf.u16(0x4240);
+    buf.u16(0xF);
+    buf.u32(0x1F44); // device serial
+    buf.str("ADL", 8); // device type
+    buf.u16(420); // device version
+    buf.u16(0xADB0);
+    buf.u32(CHANNELS.len() as u32);
+    buf.pad(4);
+    buf.str(&format!("{:02}/{:02}/{}", day, month, year), 16);
+    buf.pad(16);
+    buf.str(&format!("{:02}:{:02}:{:02}", hour, minute, second), 16);
+    buf.pad(16);
+    buf.str("", 64); // driver
+    buf.str(VEHICLE_ID, 64);
+    buf.pad(64);
+    buf.str("", 64); // venue
+    buf.pad(64);
+    buf.pad(1024);
+    buf.u32(0xC81A4); // "pro logging" magic
+    buf.pad(66);
+    buf.str("vx220-dashboard export", 64); // short comment
+    buf.pad(126);
+    debug_assert_eq!(buf.0.len(), EVENT_PTR);
+
+    // Event
+    buf.str("", 64); // name
+    buf.str("", 64); // session
+    buf.str("", 1024); // comment
+    buf.u16(VENUE_PTR as u16);
+    debug_assert_eq!(buf.0.len(), VENUE_PTR);
+
+    // Venue
+    buf.str("", 64);
+    buf.pad(1034);
+    buf.u16(VEHICLE_PTR as u16);
+    debug_assert_eq!(buf.0.len(), VEHICLE_PTR);
+
+    // Vehicle
+    buf.str(VEHICLE_ID, 64);
+    buf.pad(128);
+    buf.u32(0); // weight
+    buf.str("", 32); // type
+    buf.str("", 32); // comment
+    debug_assert_eq!(buf.0.len(), CHANNEL_META_PTR);
+
+    // Channel metadata, as a doubly linked list
+    let frequency = session.rate_hz.round().max(1.0) as u16;
+    for (i, channel) in CHANNELS.iter().enumerate() {
+        let meta_ptr = CHANNEL_META_PTR + i * CHANNEL_META_SIZE;
+        let prev = if i == 0 { 0 } else { meta_ptr - CHANNEL_META_SIZE };
+        let next = if i + 1 == CHANNELS.len() { 0 } else { meta_ptr + CHANNEL_META_SIZE };
+        buf.u32(prev as u32);
+        buf.u32(next as u32);
+        buf.u32((data_ptr + i * sample_count * 4) as u32);
+        buf.u32(sample_count as u32);
+        buf.u16(0x2EE1 + i as u16);
+        buf.u16(DTYPE_FLOAT);
+        buf.u16(DTYPE_FLOAT_SIZE);
+        buf.u16(frequency);
+        buf.i16(0); // shift
+        buf.i16(1); // multiplier
+        buf.i16(1); // scale
+        buf.i16(0); // decimal places
+        buf.str(channel.name, 32);
+        buf.str(channel.short_name, 8);
+        buf.str(channel.unit, 12);
+        buf.pad(40);
+    }
+    debug_assert_eq!(buf.0.len(), data_ptr);
+
+    // Channel data, one contiguous block per channel. i2 has no notion of a
+    // missing value, so channels hold 0 until their first sample arrives.
+    for i in 0..CHANNELS.len() {
+        for row in &session.rows {
+            let value = row[i].unwrap_or(0.0) as f32;
+            buf.0.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(&buf.0)?;
+    out.flush()?;
+
+    write_ldx(session, &path.with_extension("ldx"))
+}
+
+/// Write the `.ldx` sidecar holding lap beacons, which i2 uses to split laps
+fn write_ldx(session: &ResampledSession, path: &Path) -> Result<(), ExportError> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, r#"<?xml version="1.0"?>"#)?;
+    writeln!(out, r#"<LDXFile Locale="English_United Kingdom.1252" DefaultLocale="C" Version="1.6">"#)?;
+    writeln!(out, " <Layers>")?;
+    writeln!(out, "  <Layer>")?;
+    writeln!(out, "   <MarkerBlock>")?;
+    writeln!(out, r#"    <MarkerGroup Name="Beacons" Index="3">"#)?;
+    for (i, marker) in session.lap_markers.iter().enumerate() {
+        writeln!(
+            out,
+            r#"     <Marker Version="100" ClassName="BCN" Name="Manual.{}" Flags="77" Time="{:.1}"/>"#,
+            i + 1,
+            marker.as_micros() as f64
+        )?;
+    }
+    writeln!(out, "    </MarkerGroup>")?;
+    writeln!(out, "   </MarkerBlock>")?;
+    writeln!(out, "  </Layer>")?;
+    writeln!(out, "  <Details>")?;
+    writeln!(out, r#"   <String Id="Total Laps" Value="{}"/>"#, session.lap_markers.len())?;
+    writeln!(out, "  </Details>")?;
+    writeln!(out, " </Layers>")?;
+    writeln!(out, "</LDXFile>")?;
+    out.flush()?;
+    Ok(())
+}
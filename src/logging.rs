@@ -1,33 +1,214 @@
-use log::{debug, error, info, trace, warn, LevelFilter};
-use std::env;
+pub mod file_sink;
+
+use log::{info, Level, LevelFilter, Log, Metadata, Record};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::logging::file_sink::RotatingFileSink;
 
 pub const UI_NAMESPACE: &str = "vx220::ui";
 pub const TELEMETRY_NAMESPACE: &str = "vx220::telemetry";
 pub const RACEBOX_NAMESPACE: &str = "vx220::racebox";
 pub const ESP32_NAMESPACE: &str = "vx220::esp32";
 
-pub fn init_logging() {
-    // Set default log level if not specified in environment
-    if env::var("RUST_LOG").is_err() {
-        unsafe {
-            env::set_var("RUST_LOG", "info");
+/// Filter used when `RUST_LOG` is not set
+const DEFAULT_FILTER: &str = "info,vx220=debug";
+/// Directory for the JSON-lines log files
+const LOG_DIR: &str = "logs";
+/// Number of records kept in memory for the on-screen log viewer
+const RECENT_CAPACITY: usize = 500;
+
+/// A log record kept in memory for the dashboard
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Logger behind the `log` facade.
+///
+/// Every record that passes the filters goes to stderr (formatted by env_logger),
+/// to a rotating JSON-lines file and to an in-memory ring buffer. Filtering follows
+/// `RUST_LOG`, except for namespaces whose level was overridden at runtime.
+struct DashboardLogger {
+    /// Filter built from `RUST_LOG`, only used for its `enabled` decision
+    env_filter: env_logger::Logger,
+    /// Formatter for stderr, accepting everything the dashboard logger lets through
+    stderr: env_logger::Logger,
+    overrides: RwLock<HashMap<String, LevelFilter>>,
+    file: Mutex<Option<RotatingFileSink>>,
+    recent: Mutex<VecDeque<LogEntry>>,
+}
+
+static LOGGER: OnceLock<DashboardLogger> = OnceLock::new();
+
+impl DashboardLogger {
+    /// Level override for `target`, from the most specific matching namespace
+    fn override_for(&self, target: &str) -> Option<LevelFilter> {
+        let overrides = self.overrides.read().unwrap();
+        overrides
+            .iter()
+            .filter(|(namespace, _)| {
+                target == namespace.as_str()
+                    || (target.starts_with(namespace.as_str()) && target[namespace.len()..].starts_with("::"))
+            })
+            .max_by_key(|(namespace, _)| namespace.len())
+            .map(|(_, level)| *level)
+    }
+
+    /// Recompute the global max level so raised overrides are not filtered out by the facade
+    fn update_max_level(&self) {
+        let overrides = self.overrides.read().unwrap();
+        let max = overrides.values().copied().fold(self.env_filter.filter(), Ord::max);
+        log::set_max_level(max);
+    }
+}
+
+impl Log for DashboardLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match self.override_for(metadata.target()) {
+            Some(level) => metadata.level() <= level,
+            None => self.env_filter.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        self.stderr.log(record);
+
+        let entry = LogEntry {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+
+        if let Some(sink) = self.file.lock().unwrap().as_mut() {
+            sink.write(&entry, record.module_path());
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(entry);
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+        if let Some(sink) = self.file.lock().unwrap().as_mut() {
+            sink.flush();
         }
     }
+}
+
+pub fn init_logging() {
+    let env = env_logger::Env::default().default_filter_or(DEFAULT_FILTER);
+    let env_filter = env_logger::Builder::from_env(env).build();
 
-    // Configure env_logger
-    env_logger::Builder::from_env(env_logger::Env::default())
+    // Configure env_logger for stderr output only; filtering is done by DashboardLogger
+    let stderr = env_logger::Builder::new()
         .format_timestamp_millis()
         .format_module_path(true)
         .format_target(true)
-        .filter(Some(UI_NAMESPACE), LevelFilter::Debug)
-        .filter(Some(TELEMETRY_NAMESPACE), LevelFilter::Debug)
-        .filter(Some(RACEBOX_NAMESPACE), LevelFilter::Debug)
-        .filter(Some(ESP32_NAMESPACE), LevelFilter::Debug)
-        .init();
+        .filter_level(LevelFilter::Trace)
+        .build();
+
+    let file = match RotatingFileSink::open(LOG_DIR) {
+        Ok(sink) => Some(sink),
+        Err(e) => {
+            eprintln!("Failed to open log directory '{}': {}. Logging to stderr only.", LOG_DIR, e);
+            None
+        }
+    };
+
+    let logger = LOGGER.get_or_init(|| DashboardLogger {
+        env_filter,
+        stderr,
+        overrides: RwLock::new(HashMap::new()),
+        file: Mutex::new(file),
+        recent: Mutex::new(VecDeque::with_capacity(RECENT_CAPACITY)),
+    });
+    if log::set_logger(logger).is_err() {
+        return;
+    }
+    logger.update_max_level();
 
     info!("Logging initialized");
 }
 
+/// Expand the short namespace names accepted by the command listener
+/// (`ui`, `telemetry`, `racebox`, `esp32`) to their full log targets.
+pub fn resolve_namespace(name: &str) -> &str {
+    match name {
+        "ui" => UI_NAMESPACE,
+        "telemetry" => TELEMETRY_NAMESPACE,
+        "racebox" => RACEBOX_NAMESPACE,
+        "esp32" => ESP32_NAMESPACE,
+        other => other,
+    }
+}
+
+/// Override the level of a namespace (and everything under it) at runtime
+pub fn set_namespace_level(namespace: &str, level: LevelFilter) {
+    if let Some(logger) = LOGGER.get() {
+        logger.overrides.write().unwrap().insert(namespace.to_string(), level);
+        logger.update_max_level();
+    }
+}
+
+/// Drop a runtime override, returning the namespace to its `RUST_LOG` level
+pub fn reset_namespace_level(namespace: &str) {
+    if let Some(logger) = LOGGER.get() {
+        logger.overrides.write().unwrap().remove(namespace);
+        logger.update_max_level();
+    }
+}
+
+/// Current runtime overrides, sorted by namespace
+pub fn namespace_levels() -> Vec<(String, LevelFilter)> {
+    let Some(logger) = LOGGER.get() else {
+        return Vec::new();
+    };
+    let mut levels: Vec<_> = logger
+        .overrides
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(namespace, level)| (namespace.clone(), *level))
+        .collect();
+    levels.sort();
+    levels
+}
+
+/// The most recent records at or above `min_level` from the given namespaces,
+/// oldest first, at most `limit` of them.
+pub fn recent_entries(min_level: Level, namespaces: &[&str], limit: usize) -> Vec<LogEntry> {
+    let Some(logger) = LOGGER.get() else {
+        return Vec::new();
+    };
+    let recent = logger.recent.lock().unwrap();
+    let mut entries: Vec<LogEntry> = recent
+        .iter()
+        .rev()
+        .filter(|e| e.level <= min_level && namespaces.contains(&e.target.as_str()))
+        .take(limit)
+        .cloned()
+        .collect();
+    entries.reverse();
+    entries
+}
+
 // Convenience macros for each namespace
 #[macro_export]
 macro_rules! ui_log {
@@ -55,4 +236,4 @@ macro_rules! esp32_log {
     ($($arg:tt)*) => {
         log::log!(target: $crate::logging::ESP32_NAMESPACE, $($arg)*)
     };
-} 
\ No newline at end of file
+}
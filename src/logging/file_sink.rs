@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::logging::LogEntry;
+
+const LOG_FILE_NAME: &str = "vx220";
+/// Size at which the current file is rotated
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated files kept besides the current one (vx220.1.jsonl ... vx220.5.jsonl)
+const MAX_ROTATED_FILES: usize = 5;
+
+/// One line of the log file
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    ts_ms: u64,
+    level: &'a str,
+    target: &'a str,
+    module: Option<&'a str>,
+    msg: &'a str,
+}
+
+/// Writes log records as JSON lines to `<dir>/vx220.jsonl`, rotating by size.
+///
+/// On rotation the current file becomes `vx220.1.jsonl`, the previous `.1`
+/// becomes `.2`, and so on; the oldest file is deleted.
+pub struct RotatingFileSink {
+    dir: PathBuf,
+    writer: BufWriter<File>,
+    written: u64,
+}
+
+impl RotatingFileSink {
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let path = Self::file_path(&dir, 0);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            writer: BufWriter::new(file),
+            written,
+        })
+    }
+
+    fn file_path(dir: &Path, index: usize) -> PathBuf {
+        if index == 0 {
+            dir.join(format!("{}.jsonl", LOG_FILE_NAME))
+        } else {
+            dir.join(format!("{}.{}.jsonl", LOG_FILE_NAME, index))
+        }
+    }
+
+    pub fn write(&mut self, entry: &LogEntry, module: Option<&str>) {
+        let record = JsonRecord {
+            ts_ms: entry.timestamp_ms,
+            level: entry.level.as_str(),
+            target: &entry.target,
+            module,
+            msg: &entry.message,
+        };
+        let Ok(mut line) = serde_json::to_vec(&record) else {
+            return;
+        };
+        line.push(b'\n');
+
+        if self.written + line.len() as u64 > MAX_FILE_BYTES
+            && let Err(e) = self.rotate()
+        {
+            eprintln!("Failed to rotate log files: {}", e);
+        }
+        if self.writer.write_all(&line).is_ok() {
+            self.written += line.len() as u64;
+        }
+        // Warnings and errors are what we want to survive a crash or power cut
+        if entry.level <= log::Level::Warn {
+            let _ = self.writer.flush();
+        }
+    }
+
+    pub fn flush(&mut self) {
+        let _ = self.writer.flush();
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        let _ = fs::remove_file(Self::file_path(&self.dir, MAX_ROTATED_FILES));
+        for index in (0..MAX_ROTATED_FILES).rev() {
+            let from = Self::file_path(&self.dir, index);
+            if from.exists() {
+                fs::rename(&from, Self::file_path(&self.dir, index + 1))?;
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::file_path(&self.dir, 0))?;
+        self.writer = BufWriter::new(file);
+        self.written = 0;
+        Ok(())
+    }
+}
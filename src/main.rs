@@ -3,24 +3,86 @@ mod telemetry;
 mod racebox;
 mod esp32;
 mod logging;
+mod session;
+mod commands;
+mod cli;
+mod export;
+mod timing;
+mod tracks;
+mod performance;
+mod vehicle;
+mod settings;
 
 use winit::event_loop::EventLoop;
 use tokio::sync::Mutex;
 use std::sync::Arc;
 use log::Level::Error;
-use std::thread;
-use std::net::{TcpListener, TcpStream};
-use std::io::{BufRead, BufReader, Write};
-use crate::telemetry::{DriveMode, ColorScheme};
+use crate::cli::Command;
+use crate::session::recorder::SessionRecorder;
 
 #[tokio::main]
 async fn main() {
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Exports are one-shot conversions: no logging setup, window or live sources
+    if let Command::Export { format, session, output, rate_hz } = &command {
+        match export::run(*format, session, output, *rate_hz) {
+            Ok(()) => {
+                println!("Exported {} to {}", session.display(), output.display());
+                return;
+            }
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Initialize logging
     logging::init_logging();
 
     // Create shared telemetry state
     let telemetry_state = Arc::new(Mutex::new(telemetry::TelemetryState::new()));
 
+    match command {
+        Command::Run { record } => {
+            if let Some(path) = record {
+                let path = path.unwrap_or_else(SessionRecorder::default_path);
+                if let Err(e) = telemetry_state.lock().await.start_recording(&path) {
+                    telemetry_log!(Error, "Failed to start recording {}: {}", path.display(), e);
+                }
+            }
+            start_live_sources(telemetry_state.clone()).await;
+        }
+        Command::Replay { path, speed, paused } => {
+            if let Err(e) = session::replay::start_replay(telemetry_state.clone(), path.clone(), speed, paused).await {
+                eprintln!("Failed to open session {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        Command::Export { .. } => unreachable!("exports return before the dashboard starts"),
+    }
+
+    // Flag sources that stop delivering data
+    telemetry::health::start_health_monitor(telemetry_state.clone()).await;
+
+    // Start the command listener (in a background thread)
+    commands::start_command_listener(telemetry_state.clone());
+
+    // Create event loop
+    let event_loop = EventLoop::new();
+
+    // Run UI
+    ui::run_ui(event_loop, telemetry_state);
+}
+
+async fn start_live_sources(telemetry_state: telemetry::SharedTelemetryState) {
     // Start mock telemetry if enabled
     telemetry::maybe_start_mock_telemetry(telemetry_state.clone()).await;
 
@@ -32,12 +94,11 @@ async fn main() {
         let telemetry_state_ble_error = telemetry_state_ble.clone();
         
         racebox::ble::start_ble_listener(
-            move |data| {
+            move |packet| {
                 let state = telemetry_state_ble_data.clone();
                 tokio::spawn(async move {
                     let mut state = state.lock().await;
-                    state.latest_racebox_data = Some(data);
-                    state.clear_racebox_error(); // Clear error on successful data
+                    state.ingest_racebox_packet(&packet);
                 });
             },
             move |error| {
@@ -67,52 +128,4 @@ async fn main() {
             }
         }
     });
-
-    // Start the command listener (in a background thread)
-    start_command_listener(telemetry_state.clone());
-
-    // Create event loop
-    let event_loop = EventLoop::new();
-
-    // Run UI
-    ui::run_ui(event_loop, telemetry_state);
-}
-
-fn start_command_listener(telemetry_state: Arc<Mutex<telemetry::TelemetryState>>) {
-    thread::spawn(move || {
-        let listener = TcpListener::bind("127.0.0.1:7878").expect("Failed to bind TCP listener");
-        for stream in listener.incoming() {
-            if let Ok(stream) = stream {
-                handle_command(stream, &telemetry_state);
-            }
-        }
-    });
 }
-
-fn handle_command(mut stream: TcpStream, telemetry_state: &Arc<Mutex<telemetry::TelemetryState>>) {
-    let mut reader = BufReader::new(stream.try_clone().unwrap());
-    for line in reader.lines() {
-        if let Ok(cmd) = line {
-            let mut state = telemetry_state.blocking_lock();
-            let tokens: Vec<_> = cmd.trim().split_whitespace().collect();
-            let mut response = "OK\n".to_string();
-            if tokens.len() == 2 && tokens[0] == "set_mode" {
-                match tokens[1] {
-                    "Road" => state.set_drive_mode(DriveMode::Road),
-                    "Track" => state.set_drive_mode(DriveMode::Track),
-                    _ => response = format!("ERR invalid mode: {}\n", tokens[1]),
-                }
-            } else if tokens.len() == 2 && tokens[0] == "set_scheme" {
-                match tokens[1] {
-                    "Light" => state.set_color_scheme(ColorScheme::Light),
-                    "Dark" => state.set_color_scheme(ColorScheme::Dark),
-                    _ => response = format!("ERR invalid scheme: {}\n", tokens[1]),
-                }
-            } else {
-                response = "ERR unknown command\n".to_string();
-            }
-            let _ = stream.write_all(response.as_bytes());
-            break;
-        }
-    }
-}
\ No newline at end of file
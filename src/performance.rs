@@ -0,0 +1,440 @@
+pub mod accel;
+pub mod braking;
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::racebox::parser::RaceBoxData;
+use crate::timing::lap_timer::GnssClock;
+
+const PERFORMANCE_FILE: &str = "performance.yml";
+const RESULTS_DIR: &str = "data";
+const KPH_PER_MPH: f32 = 1.609_344;
+const M_PER_FT: f64 = 0.3048;
+/// Consecutive fixes further apart than this are not interpolated between
+const MAX_SAMPLE_GAP_S: f64 = 0.5;
+/// Runs shorter than this are too short for a meaningful slope
+const MIN_SLOPE_DISTANCE_M: f64 = 10.0;
+
+#[derive(Error, Debug)]
+pub enum PerformanceError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid performance config: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Invalid result record: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Run \"{0}\" must start from a speed")]
+    InvalidRun(String),
+}
+
+/// A speed or distance that starts or ends a run, written in YAML as a map
+/// with one key, e.g. `{ speed_kph: 100 }`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RunTargetFields", into = "RunTargetFields")]
+pub enum RunTarget {
+    SpeedKph(f32),
+    SpeedMph(f32),
+    DistanceM(f64),
+    DistanceFt(f64),
+}
+
+/// `RunTarget` as it appears in the config: serde_yaml only reads externally
+/// tagged enums from YAML tags, so the map form goes through this
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RunTargetFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed_kph: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed_mph: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    distance_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    distance_ft: Option<f64>,
+}
+
+impl TryFrom<RunTargetFields> for RunTarget {
+    type Error = String;
+
+    fn try_from(fields: RunTargetFields) -> Result<Self, Self::Error> {
+        match fields {
+            RunTargetFields { speed_kph: Some(kph), speed_mph: None, distance_m: None, distance_ft: None } => {
+                Ok(RunTarget::SpeedKph(kph))
+            }
+            RunTargetFields { speed_kph: None, speed_mph: Some(mph), distance_m: None, distance_ft: None } => {
+                Ok(RunTarget::SpeedMph(mph))
+            }
+            RunTargetFields { speed_kph: None, speed_mph: None, distance_m: Some(m), distance_ft: None } => {
+                Ok(RunTarget::DistanceM(m))
+            }
+            RunTargetFields { speed_kph: None, speed_mph: None, distance_m: None, distance_ft: Some(ft) } => {
+                Ok(RunTarget::DistanceFt(ft))
+            }
+            _ => Err("expected exactly one of speed_kph, speed_mph, distance_m or distance_ft".to_string()),
+        }
+    }
+}
+
+impl From<RunTarget> for RunTargetFields {
+    fn from(target: RunTarget) -> Self {
+        let mut fields = Self::default();
+        match target {
+            RunTarget::SpeedKph(kph) => fields.speed_kph = Some(kph),
+            RunTarget::SpeedMph(mph) => fields.speed_mph = Some(mph),
+            RunTarget::DistanceM(m) => fields.distance_m = Some(m),
+            RunTarget::DistanceFt(ft) => fields.distance_ft = Some(ft),
+        }
+        fields
+    }
+}
+
+impl RunTarget {
+    pub fn speed_kph(&self) -> Option<f32> {
+        match *self {
+            RunTarget::SpeedKph(kph) => Some(kph),
+            RunTarget::SpeedMph(mph) => Some(mph * KPH_PER_MPH),
+            RunTarget::DistanceM(_) | RunTarget::DistanceFt(_) => None,
+        }
+    }
+
+    pub fn distance_m(&self) -> Option<f64> {
+        match *self {
+            RunTarget::DistanceM(m) => Some(m),
+            RunTarget::DistanceFt(ft) => Some(ft * M_PER_FT),
+            RunTarget::SpeedKph(_) | RunTarget::SpeedMph(_) => None,
+        }
+    }
+}
+
+/// An acceleration run to time, e.g. 0–100 km/h or 1/4 mile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSpec {
+    pub name: String,
+    /// Speed the run starts from; zero for a standing start
+    pub start: RunTarget,
+    pub end: RunTarget,
+}
+
+impl RunSpec {
+    fn new(name: &str, start: RunTarget, end: RunTarget) -> Self {
+        Self {
+            name: name.to_string(),
+            start,
+            end,
+        }
+    }
+
+    /// Whether the run starts from standstill
+    pub fn is_standing(&self) -> bool {
+        self.start.speed_kph().is_some_and(|kph| kph <= 0.0)
+    }
+}
+
+/// A braking test, timed from its start speed down to a stop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrakingSpec {
+    pub name: String,
+    pub start: RunTarget,
+}
+
+impl BrakingSpec {
+    fn new(name: &str, start: RunTarget) -> Self {
+        Self {
+            name: name.to_string(),
+            start,
+        }
+    }
+}
+
+/// Performance run settings, loaded from `assets/performance.yml`.
+///
+/// Example YAML format:
+///
+/// ```yaml
+/// rollout: true
+/// rollout_m: 0.3048
+/// max_slope_percent: 2.0
+/// runs:
+///   - name: 0-100 km/h
+///     start: { speed_kph: 0 }
+///     end: { speed_kph: 100 }
+///   - name: 1/4 mile
+///     start: { speed_kph: 0 }
+///     end: { distance_ft: 1320 }
+/// braking_trigger_g: 0.3
+/// braking_tests:
+///   - name: 100-0 km/h
+///     start: { speed_kph: 100 }
+/// ```
+///
+/// Runs start from a speed (`speed_kph` or `speed_mph`, zero for a standing
+/// start) and end at a speed or a distance (`distance_m` or `distance_ft`).
+/// With `rollout`, standing runs are timed from the point the car has moved
+/// `rollout_m`, as on a drag strip. Braking tests start from a speed and are
+/// triggered by braking harder than `braking_trigger_g` through it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceConfig {
+    #[serde(default)]
+    pub rollout: bool,
+    #[serde(default = "default_rollout_m")]
+    pub rollout_m: f64,
+    /// Runs climbing or descending more steeply than this are rejected
+    #[serde(default = "default_max_slope_percent")]
+    pub max_slope_percent: f64,
+    #[serde(default = "default_runs")]
+    pub runs: Vec<RunSpec>,
+    #[serde(default = "default_braking_trigger_g")]
+    pub braking_trigger_g: f32,
+    #[serde(default = "default_braking_tests")]
+    pub braking_tests: Vec<BrakingSpec>,
+}
+
+fn default_rollout_m() -> f64 {
+    M_PER_FT
+}
+
+fn default_max_slope_percent() -> f64 {
+    2.0
+}
+
+fn default_runs() -> Vec<RunSpec> {
+    vec![
+        RunSpec::new("0-60 mph", RunTarget::SpeedMph(0.0), RunTarget::SpeedMph(60.0)),
+        RunSpec::new("0-100 km/h", RunTarget::SpeedKph(0.0), RunTarget::SpeedKph(100.0)),
+        RunSpec::new("100-200 km/h", RunTarget::SpeedKph(100.0), RunTarget::SpeedKph(200.0)),
+        RunSpec::new("60 ft", RunTarget::SpeedKph(0.0), RunTarget::DistanceFt(60.0)),
+        RunSpec::new("1/8 mile", RunTarget::SpeedKph(0.0), RunTarget::DistanceFt(660.0)),
+        RunSpec::new("1/4 mile", RunTarget::SpeedKph(0.0), RunTarget::DistanceFt(1320.0)),
+    ]
+}
+
+fn default_braking_trigger_g() -> f32 {
+    0.3
+}
+
+fn default_braking_tests() -> Vec<BrakingSpec> {
+    vec![
+        BrakingSpec::new("100-0 km/h", RunTarget::SpeedKph(100.0)),
+        BrakingSpec::new("60-0 mph", RunTarget::SpeedMph(60.0)),
+    ]
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            rollout: false,
+            rollout_m: default_rollout_m(),
+            max_slope_percent: default_max_slope_percent(),
+            runs: default_runs(),
+            braking_trigger_g: default_braking_trigger_g(),
+            braking_tests: default_braking_tests(),
+        }
+    }
+}
+
+impl PerformanceConfig {
+    /// `assets/performance.yml` next to the executable, or in the current directory
+    pub fn default_path() -> PathBuf {
+        if let Ok(exe_path) = env::current_exe()
+            && let Some(exe_dir) = exe_path.parent()
+        {
+            let path = exe_dir.join("assets").join(PERFORMANCE_FILE);
+            if path.exists() {
+                return path;
+            }
+        }
+        Path::new("assets").join(PERFORMANCE_FILE)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PerformanceError> {
+        let yaml = fs::read_to_string(path)?;
+        let config: Self = serde_yaml::from_str(&yaml)?;
+        if let Some(run) = config.runs.iter().find(|r| r.start.speed_kph().is_none()) {
+            return Err(PerformanceError::InvalidRun(run.name.clone()));
+        }
+        if let Some(test) = config.braking_tests.iter().find(|t| t.start.speed_kph().is_none_or(|kph| kph <= 0.0)) {
+            return Err(PerformanceError::InvalidRun(test.name.clone()));
+        }
+        Ok(config)
+    }
+
+    /// Load the default config, falling back to the standard runs
+    pub fn load_default() -> Self {
+        let path = Self::default_path();
+        match Self::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                crate::telemetry_log!(log::Level::Warn, "Performance config {} not loaded: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// A RaceBox fix reduced to what run timing needs
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    /// GNSS time in seconds
+    pub time_s: f64,
+    pub speed_kph: f32,
+    /// Distance travelled since the stream started, integrated from speed
+    pub odometer_m: f64,
+    pub altitude_m: f64,
+    /// Longitudinal G, positive when braking
+    pub g_force_y: f32,
+}
+
+impl Sample {
+    pub fn lerp(&self, other: &Sample, fraction: f64) -> Sample {
+        Sample {
+            time_s: self.time_s + (other.time_s - self.time_s) * fraction,
+            speed_kph: self.speed_kph + (other.speed_kph - self.speed_kph) * fraction as f32,
+            odometer_m: self.odometer_m + (other.odometer_m - self.odometer_m) * fraction,
+            altitude_m: self.altitude_m + (other.altitude_m - self.altitude_m) * fraction,
+            g_force_y: self.g_force_y + (other.g_force_y - self.g_force_y) * fraction as f32,
+        }
+    }
+
+    /// Gradient from `self` to `other` in percent, positive uphill, or zero
+    /// if they are too close together for a meaningful figure
+    pub fn slope_percent(&self, other: &Sample) -> f64 {
+        let distance_m = other.odometer_m - self.odometer_m;
+        if distance_m.abs() < MIN_SLOPE_DISTANCE_M {
+            return 0.0;
+        }
+        (other.altitude_m - self.altitude_m) / distance_m * 100.0
+    }
+}
+
+/// Fraction of the way from `a` to `b` at which `target` is reached, if it is reached on the way up
+pub fn rising_crossing(a: f64, b: f64, target: f64) -> Option<f64> {
+    (a < target && b >= target).then(|| (target - a) / (b - a))
+}
+
+/// Fraction of the way from `a` to `b` at which `target` is reached, if it is reached on the way down
+pub fn falling_crossing(a: f64, b: f64, target: f64) -> Option<f64> {
+    (a > target && b <= target).then(|| (a - target) / (a - b))
+}
+
+/// What a new RaceBox fix means for runs in progress
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    /// The car moved from the first sample to the second
+    Move(Sample, Sample),
+    /// The fix was lost, or fixes stopped arriving for a while
+    Lost,
+}
+
+/// Turns RaceBox fixes into consecutive samples on GNSS time
+#[derive(Debug, Clone, Default)]
+pub struct SampleStream {
+    clock: GnssClock,
+    previous: Option<Sample>,
+}
+
+impl SampleStream {
+    /// Feed a RaceBox sample. Returns `None` while there is nothing to compare it with.
+    pub fn next(&mut self, data: &RaceBoxData) -> Option<Step> {
+        if !data.fix_ok {
+            self.previous = None;
+            return Some(Step::Lost);
+        }
+        let time_s = self.clock.seconds(data.timestamp_ms);
+        let mut sample = Sample {
+            time_s,
+            speed_kph: data.speed_kph,
+            odometer_m: 0.0,
+            altitude_m: data.msl_alt,
+            g_force_y: data.g_force_y,
+        };
+        let Some(previous) = self.previous else {
+            self.previous = Some(sample);
+            return None;
+        };
+        let dt = time_s - previous.time_s;
+        if dt <= 0.0 {
+            return None;
+        }
+        sample.odometer_m = previous.odometer_m + (previous.speed_kph + data.speed_kph) as f64 / 2.0 / 3.6 * dt;
+        self.previous = Some(sample);
+        if dt > MAX_SAMPLE_GAP_S {
+            return Some(Step::Lost);
+        }
+        Some(Step::Move(previous, sample))
+    }
+}
+
+/// Why a run was not counted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rejection {
+    /// The GNSS fix was lost (or fixes stopped arriving) during the run
+    NoFix,
+    /// The road climbed or fell by more than the configured limit, in percent
+    Slope(f64),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::NoFix => write!(f, "GNSS fix lost"),
+            Rejection::Slope(percent) => write!(f, "slope {:+.1}% too steep", percent),
+        }
+    }
+}
+
+/// Where results of `kind` runs are stored, one JSON object per line
+pub fn results_path(kind: &str) -> PathBuf {
+    Path::new(RESULTS_DIR).join(format!("{}.jsonl", kind))
+}
+
+/// Append `result` to the JSON-lines file at `path`
+pub fn append_result<T: Serialize, P: AsRef<Path>>(path: P, result: &T) -> Result<(), PerformanceError> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_vec(result)?;
+    line.push(b'\n');
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(&line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_config_loads() {
+        let config = PerformanceConfig::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join(PERFORMANCE_FILE))
+            .expect("assets/performance.yml should load");
+        assert_eq!(config.runs.len(), 6);
+        assert_eq!(config.runs[0].start, RunTarget::SpeedMph(0.0));
+        assert_eq!(config.runs[3].end, RunTarget::DistanceFt(60.0));
+    }
+
+    #[test]
+    fn shipped_braking_tests_load() {
+        let config = PerformanceConfig::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join(PERFORMANCE_FILE))
+            .expect("assets/performance.yml should load");
+        assert_eq!(config.braking_trigger_g, 0.3);
+        assert_eq!(config.braking_tests.len(), 2);
+        assert_eq!(config.braking_tests[0].start, RunTarget::SpeedKph(100.0));
+        assert_eq!(config.braking_tests[1].start, RunTarget::SpeedMph(60.0));
+    }
+
+    #[test]
+    fn run_target_needs_one_key() {
+        assert!(serde_yaml::from_str::<RunTarget>("{ speed_kph: 0, distance_m: 10 }").is_err());
+        assert!(serde_yaml::from_str::<RunTarget>("{}").is_err());
+        assert_eq!(serde_yaml::from_str::<RunTarget>("{ distance_m: 10 }").unwrap(), RunTarget::DistanceM(10.0));
+    }
+}
@@ -0,0 +1,324 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::performance::{rising_crossing, PerformanceConfig, Rejection, RunSpec, Sample, SampleStream, Step};
+use crate::racebox::parser::RaceBoxData;
+use crate::timing::history::unix_ms_now;
+
+/// Below this speed the car counts as stationary
+const ARM_SPEED_KPH: f32 = 1.0;
+/// How long the car has to stand still before standing runs are armed
+const ARM_HOLD_S: f64 = 1.0;
+/// Speed that confirms a launch, so a creep forward on the line doesn't start a run
+const LAUNCH_SPEED_KPH: f32 = 5.0;
+/// A launch not confirmed within this time is dropped
+const LAUNCH_CONFIRM_S: f64 = 3.0;
+/// A run is abandoned once the speed falls this far below its peak (the driver lifted)
+const ABORT_SPEED_DROP_KPH: f32 = 5.0;
+/// Runs still going after this long are abandoned
+const MAX_RUN_TIME_S: f64 = 60.0;
+
+/// A completed acceleration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccelResult {
+    pub name: String,
+    pub time_s: f64,
+    pub distance_m: f64,
+    pub end_speed_kph: f32,
+    /// Average gradient over the run, positive uphill
+    pub slope_percent: f64,
+    pub rollout: bool,
+    /// Wall clock time the run finished, in ms since the Unix epoch
+    pub recorded_unix_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum AccelEvent {
+    /// The car has been stationary long enough for standing runs
+    Armed,
+    /// Standing runs have started
+    Launched,
+    Completed(AccelResult),
+    Rejected { name: String, reason: Rejection },
+}
+
+/// What the timer is doing, for display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccelStatus {
+    Idle,
+    Armed,
+    Running,
+}
+
+impl fmt::Display for AccelStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccelStatus::Idle => write!(f, "idle"),
+            AccelStatus::Armed => write!(f, "armed"),
+            AccelStatus::Running => write!(f, "running"),
+        }
+    }
+}
+
+/// A run in progress
+#[derive(Debug, Clone, Copy)]
+struct Attempt {
+    /// Index into the configured runs
+    run: usize,
+    /// Where the car started moving (standing runs) or passed the start speed
+    launch: Sample,
+    /// Where timing starts: the launch, or the end of the rollout
+    start: Option<Sample>,
+    rollout: bool,
+    peak_speed_kph: f32,
+}
+
+/// Times acceleration runs from RaceBox fixes.
+///
+/// Standing runs arm themselves once the car has been stationary for a second
+/// and start when it moves off; the launch instant is found by extrapolating
+/// the speed back to zero between fixes. Rolling runs (e.g. 100–200 km/h)
+/// start whenever the speed rises through their start speed. Speed and
+/// distance targets are interpolated between fixes on GNSS time, so results
+/// don't depend on BLE delivery jitter.
+#[derive(Debug, Clone)]
+pub struct AccelTimer {
+    config: PerformanceConfig,
+    samples: SampleStream,
+    /// GNSS time the car came to a stop
+    stationary_since: Option<f64>,
+    armed: bool,
+    /// Launch seen but not yet confirmed by `LAUNCH_SPEED_KPH`
+    pending_launch: Option<Sample>,
+    attempts: Vec<Attempt>,
+    results: Vec<AccelResult>,
+    last_rejection: Option<(String, Rejection)>,
+}
+
+impl AccelTimer {
+    pub fn new(config: PerformanceConfig) -> Self {
+        Self {
+            config,
+            samples: SampleStream::default(),
+            stationary_since: None,
+            armed: false,
+            pending_launch: None,
+            attempts: Vec::new(),
+            results: Vec::new(),
+            last_rejection: None,
+        }
+    }
+
+    pub fn runs(&self) -> &[RunSpec] {
+        &self.config.runs
+    }
+
+    pub fn rollout(&self) -> bool {
+        self.config.rollout
+    }
+
+    /// Turn rollout correction on or off for runs launched from now on
+    pub fn set_rollout(&mut self, rollout: bool) {
+        self.config.rollout = rollout;
+    }
+
+    /// Forget all runs and results, keeping the config
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config.clone());
+    }
+
+    pub fn status(&self) -> AccelStatus {
+        if !self.attempts.is_empty() {
+            AccelStatus::Running
+        } else if self.armed || self.pending_launch.is_some() {
+            AccelStatus::Armed
+        } else {
+            AccelStatus::Idle
+        }
+    }
+
+    pub fn last_result(&self) -> Option<&AccelResult> {
+        self.results.last()
+    }
+
+    /// Fastest result of this session for the run called `name`
+    pub fn best(&self, name: &str) -> Option<&AccelResult> {
+        self.results
+            .iter()
+            .filter(|r| r.name == name)
+            .min_by(|a, b| a.time_s.total_cmp(&b.time_s))
+    }
+
+    pub fn last_rejection(&self) -> Option<&(String, Rejection)> {
+        self.last_rejection.as_ref()
+    }
+
+    /// Feed a RaceBox sample
+    pub fn update(&mut self, data: &RaceBoxData) -> Vec<AccelEvent> {
+        let mut events = Vec::new();
+        let (previous, sample) = match self.samples.next(data) {
+            Some(Step::Move(previous, sample)) => (previous, sample),
+            Some(Step::Lost) => {
+                self.abandon(Rejection::NoFix, &mut events);
+                return events;
+            }
+            None => return events,
+        };
+
+        self.update_launch(previous, sample, &mut events);
+        self.start_rolling_runs(previous, sample);
+
+        let mut attempts = std::mem::take(&mut self.attempts);
+        attempts.retain_mut(|attempt| self.update_attempt(attempt, previous, sample, &mut events));
+        self.attempts = attempts;
+        events
+    }
+
+    /// Arm when stationary, and start the standing runs on a confirmed launch
+    fn update_launch(&mut self, previous: Sample, sample: Sample, events: &mut Vec<AccelEvent>) {
+        if sample.speed_kph < ARM_SPEED_KPH {
+            self.pending_launch = None;
+            let since = *self.stationary_since.get_or_insert(previous.time_s);
+            if !self.armed && sample.time_s - since >= ARM_HOLD_S {
+                self.armed = true;
+                events.push(AccelEvent::Armed);
+            }
+            return;
+        }
+        self.stationary_since = None;
+
+        if self.armed && previous.speed_kph < ARM_SPEED_KPH {
+            // Extrapolate the speed back to zero for the instant the car moved off
+            let rise = (sample.speed_kph - previous.speed_kph) as f64;
+            let fraction = (-previous.speed_kph as f64 / rise).max(-1.0);
+            let mut launch = previous.lerp(&sample, fraction);
+            launch.speed_kph = 0.0;
+            self.armed = false;
+            self.pending_launch = Some(launch);
+        }
+
+        let Some(launch) = self.pending_launch else {
+            return;
+        };
+        if sample.time_s - launch.time_s > LAUNCH_CONFIRM_S {
+            self.pending_launch = None;
+        } else if sample.speed_kph >= LAUNCH_SPEED_KPH {
+            self.pending_launch = None;
+            let rollout = self.config.rollout;
+            for (run, spec) in self.config.runs.iter().enumerate() {
+                if spec.is_standing() {
+                    self.attempts.push(Attempt {
+                        run,
+                        launch,
+                        start: (!rollout).then_some(launch),
+                        rollout,
+                        peak_speed_kph: sample.speed_kph,
+                    });
+                }
+            }
+            events.push(AccelEvent::Launched);
+        }
+    }
+
+    /// Start any rolling run whose start speed was passed on the way up
+    fn start_rolling_runs(&mut self, previous: Sample, sample: Sample) {
+        for (run, spec) in self.config.runs.iter().enumerate() {
+            let Some(start_kph) = spec.start.speed_kph() else {
+                continue;
+            };
+            if spec.is_standing() || self.attempts.iter().any(|a| a.run == run) {
+                continue;
+            }
+            if let Some(fraction) =
+                rising_crossing(previous.speed_kph as f64, sample.speed_kph as f64, start_kph as f64)
+            {
+                let start = previous.lerp(&sample, fraction);
+                self.attempts.push(Attempt {
+                    run,
+                    launch: start,
+                    start: Some(start),
+                    rollout: false,
+                    peak_speed_kph: sample.speed_kph,
+                });
+            }
+        }
+    }
+
+    /// Advance `attempt` to `sample`. Returns false once it has finished or been abandoned.
+    fn update_attempt(
+        &mut self,
+        attempt: &mut Attempt,
+        previous: Sample,
+        sample: Sample,
+        events: &mut Vec<AccelEvent>,
+    ) -> bool {
+        attempt.peak_speed_kph = attempt.peak_speed_kph.max(sample.speed_kph);
+        if sample.speed_kph < attempt.peak_speed_kph - ABORT_SPEED_DROP_KPH
+            || sample.time_s - attempt.launch.time_s > MAX_RUN_TIME_S
+        {
+            return false;
+        }
+
+        let start = match attempt.start {
+            Some(start) => start,
+            None => {
+                let rollout_end = attempt.launch.odometer_m + self.config.rollout_m;
+                let Some(fraction) = rising_crossing(previous.odometer_m, sample.odometer_m, rollout_end) else {
+                    return true;
+                };
+                *attempt.start.insert(previous.lerp(&sample, fraction))
+            }
+        };
+
+        let spec = &self.config.runs[attempt.run];
+        let crossing = match (spec.end.speed_kph(), spec.end.distance_m()) {
+            (Some(kph), _) => rising_crossing(previous.speed_kph as f64, sample.speed_kph as f64, kph as f64),
+            (None, Some(m)) => rising_crossing(previous.odometer_m, sample.odometer_m, start.odometer_m + m),
+            (None, None) => None,
+        };
+        let Some(fraction) = crossing else {
+            return true;
+        };
+        let end = previous.lerp(&sample, fraction);
+        if end.time_s < start.time_s {
+            return true;
+        }
+
+        let distance_m = end.odometer_m - start.odometer_m;
+        let slope_percent = start.slope_percent(&end);
+        if slope_percent.abs() > self.config.max_slope_percent {
+            self.reject(spec.name.clone(), Rejection::Slope(slope_percent), events);
+            return false;
+        }
+
+        let result = AccelResult {
+            name: spec.name.clone(),
+            time_s: end.time_s - start.time_s,
+            distance_m,
+            end_speed_kph: end.speed_kph,
+            slope_percent,
+            rollout: attempt.rollout,
+            recorded_unix_ms: unix_ms_now(),
+        };
+        self.results.push(result.clone());
+        events.push(AccelEvent::Completed(result));
+        false
+    }
+
+    /// Drop the runs in progress, rejecting them for `reason`, and disarm
+    fn abandon(&mut self, reason: Rejection, events: &mut Vec<AccelEvent>) {
+        for attempt in std::mem::take(&mut self.attempts) {
+            let name = self.config.runs[attempt.run].name.clone();
+            self.reject(name, reason, events);
+        }
+        self.armed = false;
+        self.stationary_since = None;
+        self.pending_launch = None;
+    }
+
+    fn reject(&mut self, name: String, reason: Rejection, events: &mut Vec<AccelEvent>) {
+        self.last_rejection = Some((name.clone(), reason));
+        events.push(AccelEvent::Rejected { name, reason });
+    }
+}
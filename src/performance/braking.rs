@@ -0,0 +1,291 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::performance::{falling_crossing, BrakingSpec, PerformanceConfig, Rejection, Sample, SampleStream, Step};
+use crate::racebox::parser::RaceBoxData;
+use crate::timing::history::unix_ms_now;
+
+/// Standard gravity, for converting the slope into a deceleration
+const G_MPS2: f64 = 9.80665;
+/// Below this speed the car counts as stopped; the stop itself is extrapolated to zero
+const STOP_SPEED_KPH: f32 = 1.0;
+/// A stop is abandoned once the speed rises this far above its lowest point
+const ABORT_SPEED_RISE_KPH: f32 = 3.0;
+/// Braking lighter than this for `RELEASE_HOLD_S` means the driver let off the brakes
+const RELEASE_G: f32 = 0.1;
+const RELEASE_HOLD_S: f64 = 1.0;
+/// Stops still going after this long are abandoned
+const MAX_STOP_TIME_S: f64 = 20.0;
+
+/// A completed braking test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrakingResult {
+    pub name: String,
+    pub start_speed_kph: f32,
+    pub time_s: f64,
+    pub distance_m: f64,
+    /// Highest longitudinal deceleration, from the RaceBox accelerometer
+    pub peak_decel_g: f32,
+    /// Time-weighted mean longitudinal deceleration over the stop
+    pub mean_decel_g: f32,
+    /// Brake line pressure from the ESP32, if it was reporting
+    pub peak_brake_bar: Option<f32>,
+    pub mean_brake_bar: Option<f32>,
+    /// Average gradient over the stop, positive uphill
+    pub slope_percent: f64,
+    /// Time and distance the same stop would have taken on level ground
+    pub corrected_time_s: f64,
+    pub corrected_distance_m: f64,
+    /// Wall clock time the stop finished, in ms since the Unix epoch
+    pub recorded_unix_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum BrakingEvent {
+    Started(String),
+    Completed(BrakingResult),
+    Rejected { name: String, reason: Rejection },
+}
+
+/// What the timer is doing, for display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrakingStatus {
+    Ready,
+    Measuring,
+}
+
+impl fmt::Display for BrakingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrakingStatus::Ready => write!(f, "ready"),
+            BrakingStatus::Measuring => write!(f, "measuring"),
+        }
+    }
+}
+
+/// A stop in progress
+#[derive(Debug, Clone, Copy)]
+struct Stop {
+    /// Index into the configured tests
+    test: usize,
+    start: Sample,
+    min_speed_kph: f32,
+    /// Integral of deceleration over time, in g·s
+    decel_g_s: f64,
+    peak_decel_g: f32,
+    /// Integral of brake pressure over the time it was known, in bar·s
+    brake_bar_s: f64,
+    brake_time_s: f64,
+    peak_brake_bar: Option<f32>,
+    /// GNSS time the deceleration fell below `RELEASE_G`
+    released_since: Option<f64>,
+}
+
+impl Stop {
+    /// Add the move from `previous` to `sample` to the running figures
+    fn accumulate(&mut self, previous: &Sample, sample: &Sample, brake_bar: Option<f32>) {
+        let dt = sample.time_s - previous.time_s;
+        self.decel_g_s += (previous.g_force_y + sample.g_force_y) as f64 / 2.0 * dt;
+        self.peak_decel_g = self.peak_decel_g.max(sample.g_force_y);
+        if let Some(bar) = brake_bar {
+            self.brake_bar_s += bar as f64 * dt;
+            self.brake_time_s += dt;
+            self.peak_brake_bar = Some(self.peak_brake_bar.map_or(bar, |peak| peak.max(bar)));
+        }
+    }
+}
+
+/// Measures stopping distance and time from RaceBox fixes.
+///
+/// A test starts when the speed falls through its start speed while braking
+/// harder than the configured trigger, and ends when the car stops. The start
+/// and the stop are interpolated between fixes on GNSS time, and distance is
+/// integrated from speed. Since a slope helps or hinders the brakes, each
+/// result also carries the time and distance corrected to level ground.
+#[derive(Debug, Clone)]
+pub struct BrakingTimer {
+    tests: Vec<BrakingSpec>,
+    trigger_g: f32,
+    samples: SampleStream,
+    stops: Vec<Stop>,
+    results: Vec<BrakingResult>,
+    last_rejection: Option<(String, Rejection)>,
+}
+
+impl BrakingTimer {
+    pub fn new(config: &PerformanceConfig) -> Self {
+        Self {
+            tests: config.braking_tests.clone(),
+            trigger_g: config.braking_trigger_g,
+            samples: SampleStream::default(),
+            stops: Vec::new(),
+            results: Vec::new(),
+            last_rejection: None,
+        }
+    }
+
+    pub fn tests(&self) -> &[BrakingSpec] {
+        &self.tests
+    }
+
+    /// Forget all stops and results, keeping the config
+    pub fn reset(&mut self) {
+        self.samples = SampleStream::default();
+        self.stops.clear();
+        self.results.clear();
+        self.last_rejection = None;
+    }
+
+    pub fn status(&self) -> BrakingStatus {
+        if self.stops.is_empty() {
+            BrakingStatus::Ready
+        } else {
+            BrakingStatus::Measuring
+        }
+    }
+
+    pub fn last_result(&self) -> Option<&BrakingResult> {
+        self.results.last()
+    }
+
+    /// Shortest stop of this session for the test called `name`
+    pub fn best(&self, name: &str) -> Option<&BrakingResult> {
+        self.results
+            .iter()
+            .filter(|r| r.name == name)
+            .min_by(|a, b| a.corrected_distance_m.total_cmp(&b.corrected_distance_m))
+    }
+
+    pub fn last_rejection(&self) -> Option<&(String, Rejection)> {
+        self.last_rejection.as_ref()
+    }
+
+    /// Feed a RaceBox sample, with the latest brake pressure from the ESP32
+    pub fn update(&mut self, data: &RaceBoxData, brake_bar: Option<f32>) -> Vec<BrakingEvent> {
+        let mut events = Vec::new();
+        let (previous, sample) = match self.samples.next(data) {
+            Some(Step::Move(previous, sample)) => (previous, sample),
+            Some(Step::Lost) => {
+                for stop in std::mem::take(&mut self.stops) {
+                    let name = self.tests[stop.test].name.clone();
+                    self.last_rejection = Some((name.clone(), Rejection::NoFix));
+                    events.push(BrakingEvent::Rejected { name, reason: Rejection::NoFix });
+                }
+                return events;
+            }
+            None => return events,
+        };
+
+        self.start_stops(previous, sample, brake_bar, &mut events);
+
+        let mut stops = std::mem::take(&mut self.stops);
+        stops.retain_mut(|stop| self.update_stop(stop, previous, sample, brake_bar, &mut events));
+        self.stops = stops;
+        events
+    }
+
+    /// Start any test whose start speed was passed on the way down under braking
+    fn start_stops(&mut self, previous: Sample, sample: Sample, brake_bar: Option<f32>, events: &mut Vec<BrakingEvent>) {
+        if sample.g_force_y < self.trigger_g {
+            return;
+        }
+        for (test, spec) in self.tests.iter().enumerate() {
+            let Some(start_kph) = spec.start.speed_kph() else {
+                continue;
+            };
+            if self.stops.iter().any(|s| s.test == test) {
+                continue;
+            }
+            if let Some(fraction) =
+                falling_crossing(previous.speed_kph as f64, sample.speed_kph as f64, start_kph as f64)
+            {
+                let start = previous.lerp(&sample, fraction);
+                let mut stop = Stop {
+                    test,
+                    start,
+                    min_speed_kph: start.speed_kph,
+                    decel_g_s: 0.0,
+                    peak_decel_g: start.g_force_y,
+                    brake_bar_s: 0.0,
+                    brake_time_s: 0.0,
+                    peak_brake_bar: None,
+                    released_since: None,
+                };
+                // Only the part of this move after the start speed counts
+                stop.accumulate(&start, &sample, brake_bar);
+                self.stops.push(stop);
+                events.push(BrakingEvent::Started(spec.name.clone()));
+            }
+        }
+    }
+
+    /// Advance `stop` to `sample`. Returns false once it has finished or been abandoned.
+    fn update_stop(
+        &mut self,
+        stop: &mut Stop,
+        previous: Sample,
+        sample: Sample,
+        brake_bar: Option<f32>,
+        events: &mut Vec<BrakingEvent>,
+    ) -> bool {
+        if previous.time_s >= stop.start.time_s {
+            stop.accumulate(&previous, &sample, brake_bar);
+        }
+        stop.min_speed_kph = stop.min_speed_kph.min(sample.speed_kph);
+        if sample.g_force_y < RELEASE_G {
+            stop.released_since.get_or_insert(sample.time_s);
+        } else {
+            stop.released_since = None;
+        }
+        if sample.speed_kph > stop.min_speed_kph + ABORT_SPEED_RISE_KPH
+            || stop.released_since.is_some_and(|since| sample.time_s - since >= RELEASE_HOLD_S)
+            || sample.time_s - stop.start.time_s > MAX_STOP_TIME_S
+        {
+            return false;
+        }
+        if sample.speed_kph >= STOP_SPEED_KPH {
+            return true;
+        }
+
+        // Extrapolate the last move down to standstill
+        let fraction = if previous.speed_kph > sample.speed_kph {
+            (previous.speed_kph / (previous.speed_kph - sample.speed_kph)).min(2.0) as f64
+        } else {
+            1.0
+        };
+        let end = previous.lerp(&sample, fraction);
+        let time_s = end.time_s - stop.start.time_s;
+        let distance_m = end.odometer_m - stop.start.odometer_m;
+        if time_s <= 0.0 || distance_m <= 0.0 {
+            return false;
+        }
+
+        // Uphill, gravity adds to the brakes; level ground needs that much more distance
+        let slope_percent = stop.start.slope_percent(&end);
+        let grade = slope_percent / 100.0;
+        let start_speed_mps = stop.start.speed_kph as f64 / 3.6;
+        let measured_decel = start_speed_mps * start_speed_mps / (2.0 * distance_m);
+        let level_decel = measured_decel - G_MPS2 * grade / (1.0 + grade * grade).sqrt();
+        let correction = if level_decel > 0.0 { measured_decel / level_decel } else { 1.0 };
+
+        let spec = &self.tests[stop.test];
+        let result = BrakingResult {
+            name: spec.name.clone(),
+            start_speed_kph: stop.start.speed_kph,
+            time_s,
+            distance_m,
+            peak_decel_g: stop.peak_decel_g,
+            mean_decel_g: (stop.decel_g_s / time_s) as f32,
+            peak_brake_bar: stop.peak_brake_bar,
+            mean_brake_bar: (stop.brake_time_s > 0.0).then(|| (stop.brake_bar_s / stop.brake_time_s) as f32),
+            slope_percent,
+            corrected_time_s: time_s * correction,
+            corrected_distance_m: distance_m * correction,
+            recorded_unix_ms: unix_ms_now(),
+        };
+        self.results.push(result.clone());
+        events.push(BrakingEvent::Completed(result));
+        false
+    }
+}
@@ -6,7 +6,6 @@ use std::time::Duration;
 use thiserror::Error;
 
 use crate::racebox::protocol::*;
-use crate::racebox::parser::{parse_packet, RaceBoxData};
 
 #[derive(Error, Debug)]
 pub enum BleError {
@@ -44,9 +43,13 @@ impl From<btleplug::Error> for BleError {
     }
 }
 
-pub fn start_ble_listener<F, E>(mut on_data: F, mut on_error: E)
+/// Connect to the RaceBox Micro and hand every raw notification payload to `on_packet`.
+///
+/// Packets are passed through unparsed so they can be recorded as-is; parsing
+/// happens in `TelemetryState::ingest_racebox_packet`.
+pub fn start_ble_listener<F, E>(mut on_packet: F, mut on_error: E)
 where
-    F: FnMut(RaceBoxData) + Send + 'static,
+    F: FnMut(Vec<u8>) + Send + 'static,
     E: FnMut(BleError) + Send + 'static,
 {
     task::spawn(async move {
@@ -200,12 +203,7 @@ where
                                 crate::racebox_log!(log::Level::Info, "Listening for notifications");
                                 while let Some(data) = notifications.next().await {
                                     //crate::racebox_log!(log::Level::Trace, "Notification received: {:x?}", data.value);
-                                    if let Some(parsed) = parse_packet(&data.value) {
-                                        //crate::racebox_log!(log::Level::Debug, "Parsed RaceBox data: {:?}", parsed);
-                                        on_data(parsed);
-                                    } else {
-                                        crate::racebox_log!(log::Level::Warn, "Failed to parse RaceBox packet");
-                                    }
+                                    on_packet(data.value);
                                 }
                             } else {
                                 crate::racebox_log!(log::Level::Error, "UART service not found");
@@ -31,6 +31,30 @@ pub struct RaceBoxData {
     pub rot_rate_z: f32,
 }
 
+/// How far the GNSS solution can be trusted, worst first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GnssQuality {
+    NoFix,
+    Poor,
+    Fair,
+    Good,
+}
+
+impl RaceBoxData {
+    /// Rate the fix from its satellite count and speed accuracy
+    pub fn gnss_quality(&self) -> GnssQuality {
+        if !self.fix_ok {
+            GnssQuality::NoFix
+        } else if self.num_sv >= 10 && self.speed_acc <= 0.5 {
+            GnssQuality::Good
+        } else if self.num_sv >= 6 && self.speed_acc <= 1.5 {
+            GnssQuality::Fair
+        } else {
+            GnssQuality::Poor
+        }
+    }
+}
+
 pub fn parse_packet(data: &[u8]) -> Option<RaceBoxData> {
     if data.len() < 80 || data[0] != 0xB5 || data[1] != 0x62 || data[2] != 0xFF || data[3] != 0x01 {
         return None;
@@ -0,0 +1,60 @@
+pub mod file;
+pub mod recorder;
+pub mod replay;
+
+use std::time::Duration;
+use thiserror::Error;
+
+/// Where a recorded packet came from. The raw bytes are stored exactly as they
+/// were received so replay can push them back through the same parsers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordSource {
+    /// RaceBox Micro BLE notification payload
+    RaceBox,
+    /// ESP32 UART frame, from the 0xAA header to the 0x55 EOF byte
+    Esp32,
+    /// Manual beacon marker, with an empty payload
+    Marker,
+}
+
+impl RecordSource {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x01 => Some(RecordSource::RaceBox),
+            0x02 => Some(RecordSource::Esp32),
+            0x03 => Some(RecordSource::Marker),
+            _ => None,
+        }
+    }
+
+    pub fn to_byte(self) -> u8 {
+        match self {
+            RecordSource::RaceBox => 0x01,
+            RecordSource::Esp32 => 0x02,
+            RecordSource::Marker => 0x03,
+        }
+    }
+}
+
+/// A single raw packet, stamped with its offset from the start of the session
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    pub offset: Duration,
+    pub source: RecordSource,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Not a VX220 session file")]
+    BadMagic,
+
+    #[error("Unsupported session file version {0}")]
+    UnsupportedVersion(u16),
+
+    #[error("Unknown record source 0x{0:02X}")]
+    UnknownSource(u8),
+}
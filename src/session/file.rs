@@ -0,0 +1,108 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::session::{RecordSource, SessionError, SessionRecord};
+
+// Session file layout (all integers little-endian):
+//
+// | MAGIC "VX220SES" | VERSION u16 | STARTED_UNIX_MS u64 |
+// followed by any number of records:
+// | OFFSET_US u64 | SOURCE u8 | LEN u16 | PAYLOAD (LEN bytes) |
+const MAGIC: &[u8; 8] = b"VX220SES";
+const VERSION: u16 = 1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SessionHeader {
+    /// Wall clock time the recording started, in milliseconds since the Unix epoch
+    pub started_unix_ms: u64,
+}
+
+pub struct SessionWriter {
+    writer: BufWriter<File>,
+}
+
+impl SessionWriter {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, SessionError> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        let started_unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&started_unix_ms.to_le_bytes())?;
+        Ok(Self { writer })
+    }
+
+    pub fn write_record(&mut self, offset: Duration, source: RecordSource, payload: &[u8]) -> Result<(), SessionError> {
+        let len = payload.len().min(u16::MAX as usize);
+        self.writer.write_all(&(offset.as_micros() as u64).to_le_bytes())?;
+        self.writer.write_all(&[source.to_byte()])?;
+        self.writer.write_all(&(len as u16).to_le_bytes())?;
+        self.writer.write_all(&payload[..len])?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), SessionError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read a whole session file into memory.
+///
+/// Sessions are small enough (a few MB for a full track day stint) that keeping
+/// them in memory makes seeking trivial. A truncated trailing record, as left by
+/// a power cut mid-write, is silently dropped.
+pub fn read_session<P: AsRef<Path>>(path: P) -> Result<(SessionHeader, Vec<SessionRecord>), SessionError> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(SessionError::BadMagic);
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version != VERSION {
+        return Err(SessionError::UnsupportedVersion(version));
+    }
+    let mut started = [0u8; 8];
+    reader.read_exact(&mut started)?;
+    let header = SessionHeader {
+        started_unix_ms: u64::from_le_bytes(started),
+    };
+
+    let mut records = Vec::new();
+    loop {
+        let mut record_header = [0u8; 11];
+        match reader.read_exact(&mut record_header) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let offset_us = u64::from_le_bytes(record_header[0..8].try_into().unwrap());
+        let source = RecordSource::from_byte(record_header[8])
+            .ok_or(SessionError::UnknownSource(record_header[8]))?;
+        let len = u16::from_le_bytes([record_header[9], record_header[10]]) as usize;
+        let mut payload = vec![0u8; len];
+        match reader.read_exact(&mut payload) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        records.push(SessionRecord {
+            offset: Duration::from_micros(offset_us),
+            source,
+            payload,
+        });
+    }
+
+    Ok((header, records))
+}
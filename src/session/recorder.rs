@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::session::file::SessionWriter;
+use crate::session::{RecordSource, SessionError};
+
+const SESSION_DIR: &str = "sessions";
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Writes every raw packet that enters the telemetry pipeline to a session file
+pub struct SessionRecorder {
+    path: PathBuf,
+    writer: SessionWriter,
+    started_at: Duration,
+    last_flush: Duration,
+    records_written: u64,
+}
+
+impl SessionRecorder {
+    /// Start recording to `path`. `now` is the telemetry clock at the start of the recording.
+    pub fn start<P: AsRef<Path>>(path: P, now: Duration) -> Result<Self, SessionError> {
+        let path = path.as_ref().to_path_buf();
+        let writer = SessionWriter::create(&path)?;
+        crate::telemetry_log!(log::Level::Info, "Recording session to {}", path.display());
+        Ok(Self {
+            path,
+            writer,
+            started_at: now,
+            last_flush: now,
+            records_written: 0,
+        })
+    }
+
+    /// Default location for a new recording: `sessions/<unix seconds>.vxs`
+    pub fn default_path() -> PathBuf {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Path::new(SESSION_DIR).join(format!("{}.vxs", secs))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn records_written(&self) -> u64 {
+        self.records_written
+    }
+
+    pub fn record(&mut self, now: Duration, source: RecordSource, payload: &[u8]) {
+        let offset = now.saturating_sub(self.started_at);
+        if let Err(e) = self.writer.write_record(offset, source, payload) {
+            crate::telemetry_log!(log::Level::Error, "Failed to write session record: {}", e);
+            return;
+        }
+        self.records_written += 1;
+
+        // Flush regularly so a power cut loses at most a second of data
+        if now.saturating_sub(self.last_flush) >= FLUSH_INTERVAL {
+            if let Err(e) = self.writer.flush() {
+                crate::telemetry_log!(log::Level::Error, "Failed to flush session file: {}", e);
+            }
+            self.last_flush = now;
+        }
+    }
+
+    pub fn stop(mut self) {
+        if let Err(e) = self.writer.flush() {
+            crate::telemetry_log!(log::Level::Error, "Failed to flush session file: {}", e);
+        }
+        crate::telemetry_log!(
+            log::Level::Info,
+            "Stopped recording {} ({} records)",
+            self.path.display(),
+            self.records_written
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::session::file::{read_session, SessionHeader};
+use crate::session::{RecordSource, SessionError, SessionRecord};
+use crate::telemetry::{SharedTelemetryState, TelemetryState};
+
+const REPLAY_TICK: Duration = Duration::from_millis(10);
+/// Fastest replay speed accepted from the command line and the `replay speed` command
+pub const MAX_REPLAY_SPEED: f32 = 64.0;
+
+/// Whether `speed` is a usable replay speed multiplier
+pub fn valid_replay_speed(speed: f32) -> bool {
+    speed.is_finite() && speed > 0.0 && speed <= MAX_REPLAY_SPEED
+}
+
+/// Playback state of a running replay.
+///
+/// Lives in `TelemetryState` so the command listener can pause, seek or change
+/// the speed while the replay task is running.
+#[derive(Debug, Clone)]
+pub struct ReplayControl {
+    pub path: PathBuf,
+    /// Wall clock time the session was recorded, in milliseconds since the Unix epoch
+    pub recorded_unix_ms: u64,
+    pub duration: Duration,
+    pub position: Duration,
+    pub speed: f32,
+    pub paused: bool,
+    pub finished: bool,
+    /// Pending seek request, consumed by the replay task on its next tick
+    pub seek_to: Option<Duration>,
+}
+
+/// Steps through a recorded session and feeds each packet into a `TelemetryState`.
+///
+/// The packets go through `ingest_racebox_packet` / `ingest_esp32_frame`, exactly
+/// like live BLE and UART data, with the telemetry clock set to the recorded offset.
+/// Feeding the same session therefore always produces the same derived state.
+pub struct Replayer {
+    header: SessionHeader,
+    records: Vec<SessionRecord>,
+    next: usize,
+}
+
+impl Replayer {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SessionError> {
+        let (header, records) = read_session(path)?;
+        Ok(Self { header, records, next: 0 })
+    }
+
+    pub fn header(&self) -> SessionHeader {
+        self.header
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.records.last().map(|r| r.offset).unwrap_or_default()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.records.len()
+    }
+
+    pub fn rewind(&mut self) {
+        self.next = 0;
+    }
+
+    /// Feed the next record, if any, and return its source
+    pub fn step(&mut self, state: &mut TelemetryState) -> Option<RecordSource> {
+        let record = self.records.get(self.next)?;
+        self.next += 1;
+        feed_record(state, record);
+        Some(record.source)
+    }
+
+    /// Feed every record up to and including `position`, then leave the
+    /// telemetry clock at `position`. Returns the number of records fed.
+    pub fn advance_to(&mut self, state: &mut TelemetryState, position: Duration) -> usize {
+        let mut fed = 0;
+        while let Some(record) = self.records.get(self.next) {
+            if record.offset > position {
+                break;
+            }
+            feed_record(state, record);
+            self.next += 1;
+            fed += 1;
+        }
+        state.set_replay_time(position);
+        fed
+    }
+
+    /// Jump to `position`. Seeking backwards resets the session and fast-forwards
+    /// from the start so derived state is rebuilt exactly as it was.
+    pub fn seek(&mut self, state: &mut TelemetryState, current: Duration, position: Duration) {
+        if position < current {
+            state.reset_session();
+            self.rewind();
+        }
+        self.advance_to(state, position);
+    }
+}
+
+fn feed_record(state: &mut TelemetryState, record: &SessionRecord) {
+    state.set_replay_time(record.offset);
+    match record.source {
+        RecordSource::RaceBox => {
+            state.ingest_racebox_packet(&record.payload);
+        }
+        RecordSource::Esp32 => {
+            let _ = state.ingest_esp32_frame(&record.payload);
+        }
+        RecordSource::Marker => state.ingest_marker(),
+    }
+}
+
+/// Start replaying a session file into the shared telemetry state.
+///
+/// Playback is driven by wall time scaled by the current speed, and can be
+/// controlled at any time through `TelemetryState::replay`.
+pub async fn start_replay(
+    telemetry_state: SharedTelemetryState,
+    path: PathBuf,
+    speed: f32,
+    paused: bool,
+) -> Result<(), SessionError> {
+    let mut replayer = Replayer::open(&path)?;
+    let duration = replayer.duration();
+    crate::telemetry_log!(
+        log::Level::Info,
+        "Replaying {} ({:.1}s, {} records)",
+        path.display(),
+        duration.as_secs_f32(),
+        replayer.records.len()
+    );
+
+    {
+        let mut state = telemetry_state.lock().await;
+        state.reset_session();
+        state.set_replay_time(Duration::ZERO);
+        state.replay = Some(ReplayControl {
+            path,
+            recorded_unix_ms: replayer.header().started_unix_ms,
+            duration,
+            position: Duration::ZERO,
+            speed,
+            paused,
+            finished: false,
+            seek_to: None,
+        });
+    }
+
+    tokio::spawn(async move {
+        let mut position = Duration::ZERO;
+        let mut last_tick = Instant::now();
+        loop {
+            sleep(REPLAY_TICK).await;
+            let elapsed = last_tick.elapsed();
+            last_tick = Instant::now();
+
+            let mut state = telemetry_state.lock().await;
+            let (speed, paused, seek_to) = match state.replay.as_mut() {
+                Some(control) => (control.speed, control.paused, control.seek_to.take()),
+                None => {
+                    crate::telemetry_log!(log::Level::Info, "Replay stopped");
+                    break;
+                }
+            };
+
+            if let Some(target) = seek_to {
+                let target = target.min(duration);
+                replayer.seek(&mut state, position, target);
+                position = target;
+            } else if !paused && !replayer.is_finished() {
+                position = (position + elapsed.mul_f32(speed.max(0.0))).min(duration);
+                replayer.advance_to(&mut state, position);
+            }
+
+            let finished = replayer.is_finished();
+            if let Some(control) = state.replay.as_mut() {
+                if finished && !control.finished {
+                    crate::telemetry_log!(log::Level::Info, "Replay reached end of session");
+                }
+                control.position = position;
+                control.finished = finished;
+            }
+        }
+    });
+
+    Ok(())
+}
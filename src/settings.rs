@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::telemetry::inputs::HISTORY_SPAN;
+
+const SETTINGS_DIR: &str = "data";
+const SETTINGS_FILE: &str = "settings.yml";
+/// Largest friction circle limit accepted, in G
+pub const MAX_FRICTION_LIMIT_G: f32 = 5.0;
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid settings: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedUnit {
+    #[default]
+    Kph,
+    Mph,
+}
+
+impl SpeedUnit {
+    /// Convert a speed in km/h to this unit
+    pub fn convert_kph(self, kph: f32) -> f32 {
+        match self {
+            SpeedUnit::Kph => kph,
+            SpeedUnit::Mph => kph / 1.609_344,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SpeedUnit::Kph => "km/h",
+            SpeedUnit::Mph => "mph",
+        }
+    }
+}
+
+/// Where the displayed speed comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedSource {
+    /// Vehicle speed from the ESP32
+    Wheel,
+    /// RaceBox GNSS speed
+    Gnss,
+    /// GNSS speed while the fix is good enough, wheel speed otherwise
+    #[default]
+    Fused,
+}
+
+impl fmt::Display for SpeedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpeedSource::Wheel => write!(f, "wheel"),
+            SpeedSource::Gnss => write!(f, "gnss"),
+            SpeedSource::Fused => write!(f, "fused"),
+        }
+    }
+}
+
+/// Display preferences changed from the command interface, kept in
+/// `data/settings.yml` so they survive a restart.
+///
+/// Example YAML format:
+///
+/// ```yaml
+/// speed_unit: mph
+/// speed_source: fused
+/// speed_arc: true
+/// trace_seconds: 10
+/// friction_circle: true
+/// friction_limit_g: 1.2
+/// map_speed_colors: false
+/// map_ghost: true
+/// ```
+///
+/// Every field is optional; missing ones take their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub speed_unit: SpeedUnit,
+    pub speed_source: SpeedSource,
+    /// Show the speedometer as an arc gauge around the digital readout
+    pub speed_arc: bool,
+    /// Length of the driver input trace
+    pub trace_seconds: f32,
+    /// Show combined G on the G-force meter against `friction_limit_g`
+    pub friction_circle: bool,
+    /// Most combined G the car can pull, the radius of the friction circle
+    pub friction_limit_g: f32,
+    /// Colour the lap in progress on the track map by speed
+    pub map_speed_colors: bool,
+    /// Show where the reference lap was on the track map
+    pub map_ghost: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            speed_unit: SpeedUnit::default(),
+            speed_source: SpeedSource::default(),
+            speed_arc: false,
+            trace_seconds: 10.0,
+            friction_circle: false,
+            friction_limit_g: 1.2,
+            map_speed_colors: false,
+            map_ghost: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn default_path() -> PathBuf {
+        Path::new(SETTINGS_DIR).join(SETTINGS_FILE)
+    }
+
+    /// Load the settings at `path`; a missing file gives the defaults
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SettingsError> {
+        match fs::read_to_string(path) {
+            Ok(yaml) => {
+                let mut settings: Self = serde_yaml::from_str(&yaml)?;
+                settings.replace_invalid();
+                Ok(settings)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Whether `seconds` is a usable input trace length
+    pub fn valid_trace_seconds(seconds: f32) -> bool {
+        (1.0..=HISTORY_SPAN.as_secs_f32()).contains(&seconds)
+    }
+
+    /// Whether `g` is a usable friction circle limit
+    pub fn valid_friction_limit_g(g: f32) -> bool {
+        g > 0.0 && g <= MAX_FRICTION_LIMIT_G
+    }
+
+    /// Put back the default for any value the file was edited out of range
+    fn replace_invalid(&mut self) {
+        let defaults = Self::default();
+        if !Self::valid_trace_seconds(self.trace_seconds) {
+            crate::telemetry_log!(
+                log::Level::Warn,
+                "Invalid trace_seconds {} in settings, using {}",
+                self.trace_seconds,
+                defaults.trace_seconds
+            );
+            self.trace_seconds = defaults.trace_seconds;
+        }
+        if !Self::valid_friction_limit_g(self.friction_limit_g) {
+            crate::telemetry_log!(
+                log::Level::Warn,
+                "Invalid friction_limit_g {} in settings, using {}",
+                self.friction_limit_g,
+                defaults.friction_limit_g
+            );
+            self.friction_limit_g = defaults.friction_limit_g;
+        }
+    }
+
+    /// Load the default settings file, falling back to the defaults
+    pub fn load_default() -> Self {
+        let path = Self::default_path();
+        match Self::load(&path) {
+            Ok(settings) => settings,
+            Err(e) => {
+                crate::telemetry_log!(log::Level::Warn, "Settings {} not loaded: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SettingsError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+}
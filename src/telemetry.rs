@@ -1,7 +1,33 @@
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use crate::racebox::parser::RaceBoxData;
-use std::time::Instant;
+use crate::racebox::parser::{parse_packet, RaceBoxData};
+use crate::esp32::ESP32Connection;
+use crate::session::RecordSource;
+use crate::session::recorder::SessionRecorder;
+use crate::session::replay::ReplayControl;
+use crate::session::SessionError;
+use crate::tracks::{Track, TrackDatabase};
+use crate::tracks::learner::TrackLearner;
+use crate::telemetry::health::HealthMonitor;
+use crate::telemetry::alerts::{Alert, AlertLevel};
+use crate::telemetry::tyres::{TyreMonitor, CORNERS};
+use crate::telemetry::inputs::{InputHistory, InputSample};
+use crate::telemetry::g_force::{GForceHistory, GForceSample};
+use crate::timing::delta::{LapDelta, ReferenceSource};
+use crate::timing::history::LapHistory;
+use crate::timing::pit::{PitEvent, PitMonitor};
+use crate::timing::stint::{format_stint_time, StintManager};
+use crate::timing::geo::GeoPoint;
+use crate::performance::{self, PerformanceConfig};
+use crate::vehicle::VehicleConfig;
+use crate::settings::{Settings, SpeedSource};
+use crate::racebox::parser::GnssQuality;
+use crate::performance::accel::{AccelEvent, AccelTimer};
+use crate::performance::braking::{BrakingEvent, BrakingTimer};
+use crate::timing::lap_timer::{format_delta, format_lap_time, LapEvent, LapTimer, SplitResult, TimingLine};
+use std::error::Error;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 /// Status flags from the ESP32, representing various vehicle warning states
 #[derive(Debug, Clone, Copy, Default)]
@@ -68,6 +94,83 @@ pub struct ESP32Data {
     pub gear_position: Option<u8>,
     pub tyre_pressures: [Option<u16>; 4],
     pub tyre_temps: [Option<i16>; 4],
+    pub coolant_temp: Option<i16>,
+    pub oil_temp: Option<i16>,
+}
+
+/// Engineering-unit accessors for the raw values sent by the ESP32.
+/// Scales follow docs/esp32-payload.md.
+impl ESP32Data {
+    /// Boost pressure in bar
+    pub fn boost_bar(&self) -> Option<f32> {
+        self.boost_pressure.map(|v| v as f32 / 1000.0)
+    }
+
+    /// Steering angle in degrees (0.1°/LSB), positive to the right
+    pub fn steering_angle_deg(&self) -> Option<f32> {
+        self.steering_angle.map(|v| v as f32 / 10.0)
+    }
+
+    /// Brake pressure in bar (0.01 bar/LSB)
+    pub fn brake_pressure_bar(&self) -> Option<f32> {
+        self.brake_pressure.map(|v| v as f32 / 100.0)
+    }
+
+    /// Tyre pressure in bar (0.01 bar/LSB), indexed FL, FR, RL, RR
+    pub fn tyre_pressure_bar(&self, corner: usize) -> Option<f32> {
+        self.tyre_pressures[corner].map(|v| v as f32 / 100.0)
+    }
+
+    /// Tyre temperature in °C (0.1°C/LSB), indexed FL, FR, RL, RR
+    pub fn tyre_temp_c(&self, corner: usize) -> Option<f32> {
+        self.tyre_temps[corner].map(|v| v as f32 / 10.0)
+    }
+
+    /// Coolant temperature in °C (0.1°C/LSB)
+    pub fn coolant_temp_c(&self) -> Option<f32> {
+        self.coolant_temp.map(|v| v as f32 / 10.0)
+    }
+
+    /// Oil temperature in °C (0.1°C/LSB)
+    pub fn oil_temp_c(&self) -> Option<f32> {
+        self.oil_temp.map(|v| v as f32 / 10.0)
+    }
+
+    /// Selected gear (0 = neutral, 1–6, 7 = reverse)
+    pub fn gear(&self) -> Option<Gear> {
+        self.gear_position.and_then(Gear::from_position)
+    }
+}
+
+/// A gearbox position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gear {
+    Neutral,
+    /// Forward gear, 1 for first
+    Forward(u8),
+    Reverse,
+}
+
+impl Gear {
+    /// Decode the ESP32's gear position byte
+    pub fn from_position(position: u8) -> Option<Self> {
+        match position {
+            0 => Some(Gear::Neutral),
+            1..=6 => Some(Gear::Forward(position)),
+            7 => Some(Gear::Reverse),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Gear {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Gear::Neutral => write!(f, "N"),
+            Gear::Forward(gear) => write!(f, "{}", gear),
+            Gear::Reverse => write!(f, "R"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -89,24 +192,656 @@ pub enum ColorScheme {
     // HighContrast, // for future use
 }
 
+/// Which page the dashboard shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardPage {
+    Main,
+    Tyres,
+    Map,
+    Diagnostics,
+}
+
+/// Time base for everything derived from incoming samples.
+///
+/// Live sources follow the wall clock. During replay the clock is driven by the
+/// recorded packet offsets, so derived state comes out exactly as it did on track.
+#[derive(Debug, Clone, Copy)]
+pub enum TelemetryClock {
+    Live(Instant),
+    Replay(Duration),
+}
+
 pub struct TelemetryState {
+    pub clock: TelemetryClock,
     pub latest_racebox_data: Option<RaceBoxData>,
     pub latest_esp32_data: ESP32Data,
     pub racebox_error: Option<(TelemetryError, Instant)>,
     pub esp32_error: Option<(TelemetryError, Instant)>,
     pub drive_mode: DriveMode,
     pub color_scheme: ColorScheme,
+    pub page: DashboardPage,
+    pub health: HealthMonitor,
+    pub recorder: Option<SessionRecorder>,
+    pub replay: Option<ReplayControl>,
+    /// Manual beacon markers (e.g. driver button presses), on the telemetry clock
+    pub markers: Vec<Duration>,
+    /// Lap timing, active once a start/finish line is configured
+    pub lap_timer: Option<LapTimer>,
+    /// Live delta against the reference lap
+    pub lap_delta: LapDelta,
+    pub tracks: TrackDatabase,
+    /// Track the lap timer is set up for, when it came from the database
+    pub active_track: Option<Track>,
+    /// Pit lane detection, when the active track has a pit lane
+    pub pit_monitor: Option<PitMonitor>,
+    /// Whether tracks are selected automatically from the GNSS position.
+    /// Turned off when timing lines are set by hand.
+    pub track_detection: bool,
+    /// Set while a new track is being learned from a driven lap
+    pub track_learner: Option<TrackLearner>,
+    /// Every lap timed at a known track, across runs
+    pub lap_history: LapHistory,
+    pub performance: PerformanceConfig,
+    /// Acceleration run timing, active once turned on with `accel on`
+    pub accel_timer: Option<AccelTimer>,
+    /// Braking test measurement, active once turned on with `braking on`
+    pub braking_timer: Option<BrakingTimer>,
+    pub vehicle: VehicleConfig,
+    /// Stint time, laps and fuel, restarted on every pit exit
+    pub stint: StintManager,
+    /// Slow leak detection from the tyre pressures
+    pub tyres: TyreMonitor,
+    /// Recent throttle, brake and steering, for the input trace
+    pub inputs: InputHistory,
+    /// G-force trail and session peaks, for the G-force meter
+    pub g_force: GForceHistory,
+    /// Display preferences, saved whenever they change
+    pub settings: Settings,
+}
+
+/// A road speed and where it came from
+#[derive(Debug, Clone, Copy)]
+pub struct SpeedReading {
+    pub kph: f32,
+    /// `Wheel` or `Gnss`, even when the setting is `Fused`
+    pub source: SpeedSource,
+    /// Quality of the fix, for GNSS speeds
+    pub gnss_quality: Option<GnssQuality>,
 }
 
 impl TelemetryState {
     pub fn new() -> Self {
+        let vehicle = VehicleConfig::load_default();
         Self {
+            clock: TelemetryClock::Live(Instant::now()),
             latest_racebox_data: None,
             latest_esp32_data: ESP32Data::default(),
             racebox_error: None,
             esp32_error: None,
             drive_mode: DriveMode::Road,
             color_scheme: ColorScheme::Light,
+            page: DashboardPage::Main,
+            health: HealthMonitor::default(),
+            recorder: None,
+            replay: None,
+            markers: Vec::new(),
+            lap_timer: None,
+            lap_delta: LapDelta::default(),
+            tracks: TrackDatabase::load_default(),
+            active_track: None,
+            pit_monitor: None,
+            track_detection: true,
+            track_learner: None,
+            lap_history: LapHistory::open(LapHistory::default_path()),
+            performance: PerformanceConfig::load_default(),
+            accel_timer: None,
+            braking_timer: None,
+            stint: StintManager::new(&vehicle, Duration::ZERO),
+            tyres: TyreMonitor::new(&vehicle.tyres, Duration::ZERO),
+            inputs: InputHistory::default(),
+            g_force: GForceHistory::default(),
+            vehicle,
+            settings: Settings::load_default(),
+        }
+    }
+
+    /// Current time on the telemetry clock
+    pub fn now(&self) -> Duration {
+        match self.clock {
+            TelemetryClock::Live(started) => started.elapsed(),
+            TelemetryClock::Replay(position) => position,
+        }
+    }
+
+    /// Switch to (or advance) the replay clock
+    pub fn set_replay_time(&mut self, position: Duration) {
+        self.clock = TelemetryClock::Replay(position);
+    }
+
+    /// Record, parse and apply a raw RaceBox packet.
+    ///
+    /// This is the single entry point for RaceBox data, used by the BLE listener
+    /// and by session replay alike. Returns false if the packet could not be parsed.
+    pub fn ingest_racebox_packet(&mut self, packet: &[u8]) -> bool {
+        self.record(RecordSource::RaceBox, packet);
+        match parse_packet(packet) {
+            Some(data) => {
+                self.apply_racebox_data(data);
+                true
+            }
+            None => {
+                self.health.racebox.record_parse_failure();
+                crate::racebox_log!(log::Level::Warn, "Failed to parse RaceBox packet");
+                false
+            }
+        }
+    }
+
+    /// Record, parse and apply a raw ESP32 UART frame.
+    ///
+    /// Like `ingest_racebox_packet`, this is shared by the UART listener and replay.
+    pub fn ingest_esp32_frame(&mut self, frame: &[u8]) -> Result<ESP32Data, Box<dyn Error + Send + Sync>> {
+        self.record(RecordSource::Esp32, frame);
+        let data = ESP32Connection::parse_frame(frame).inspect_err(|_| self.health.esp32.record_parse_failure())?;
+        self.apply_esp32_data(data.clone());
+        Ok(data)
+    }
+
+    /// Record a manual beacon marker at the current time
+    pub fn ingest_marker(&mut self) {
+        self.record(RecordSource::Marker, &[]);
+        self.markers.push(self.now());
+        self.mark_learned_start_finish();
+    }
+
+    /// Apply an already decoded RaceBox sample
+    pub fn apply_racebox_data(&mut self, data: RaceBoxData) {
+        self.update_track_learner(&data);
+        self.detect_track(&data);
+        self.update_timing(&data);
+        self.update_pit_monitor(&data);
+        self.update_accel_timer(&data);
+        self.update_braking_timer(&data);
+        self.g_force.push(GForceSample {
+            time: self.now(),
+            x: data.g_force_x,
+            y: data.g_force_y,
+        });
+        self.latest_racebox_data = Some(data);
+        self.health.racebox.record_sample(self.now());
+        self.clear_racebox_error();
+    }
+
+    /// Start learning a new track; the start/finish line is placed by the next marker
+    pub fn start_learning_track(&mut self, name: String) {
+        crate::telemetry_log!(log::Level::Info, "Learning track {}: mark the start/finish line", name);
+        self.track_learner = Some(TrackLearner::new(name));
+    }
+
+    /// Place the learned track's start/finish line at the car's current position
+    fn mark_learned_start_finish(&mut self) {
+        let now = self.now();
+        let (Some(learner), Some(data)) = (self.track_learner.as_mut(), self.latest_racebox_data.as_ref()) else {
+            return;
+        };
+        if !learner.is_recording() && data.fix_ok {
+            learner.mark_start_finish(data, now);
+            crate::telemetry_log!(log::Level::Info, "Start/finish line marked for {}", learner.name());
+        }
+    }
+
+    fn update_track_learner(&mut self, data: &RaceBoxData) {
+        let now = self.now();
+        let Some(track) = self.track_learner.as_mut().and_then(|learner| learner.update(data, now)) else {
+            return;
+        };
+        self.track_learner = None;
+        crate::telemetry_log!(
+            log::Level::Info,
+            "Learned {}: {:.0} m, {}, {} splits",
+            track.name,
+            track.length_m,
+            track.direction,
+            track.splits.len()
+        );
+        // Replays and exports learn the track again but leave the database alone
+        if matches!(self.clock, TelemetryClock::Live(_))
+            && let Err(e) = self.tracks.save_track(track.clone(), TrackDatabase::learned_path())
+        {
+            crate::telemetry_log!(log::Level::Error, "Failed to save track {}: {}", track.name, e);
+        }
+        self.track_detection = true;
+        self.select_track(track);
+    }
+
+    /// Select the track the car is at, or drop it once the car has left
+    fn detect_track(&mut self, data: &RaceBoxData) {
+        if !self.track_detection || !data.fix_ok {
+            return;
+        }
+        let position = GeoPoint::new(data.latitude, data.longitude);
+        match &self.active_track {
+            Some(track) if track.has_left(&position) => {
+                crate::telemetry_log!(log::Level::Info, "Left {}", track.name);
+                self.active_track = None;
+                self.pit_monitor = None;
+                self.lap_timer = None;
+                self.lap_delta.reset();
+            }
+            Some(_) => {}
+            None => {
+                if let Some(track) = self.tracks.detect(&position).cloned() {
+                    crate::telemetry_log!(log::Level::Info, "Detected track {}", track.name);
+                    self.select_track(track);
+                }
+            }
+        }
+    }
+
+    /// Set the lap timer up with a track's timing lines
+    pub fn select_track(&mut self, track: Track) {
+        self.lap_timer = Some(LapTimer::new(track.start_finish, track.splits.clone()));
+        self.lap_delta.reset();
+        self.pit_monitor = PitMonitor::for_track(&track);
+        self.active_track = Some(track);
+        if self.lap_delta.source() == ReferenceSource::AllTimeBest {
+            self.use_all_time_best();
+        }
+        if self.tracks.auto_track_mode {
+            self.set_drive_mode(DriveMode::Track);
+        }
+    }
+
+    /// Use the stored personal best at the active track (for the current setup) as the delta reference
+    pub fn use_all_time_best(&mut self) {
+        let reference = self
+            .active_track
+            .as_ref()
+            .and_then(|track| self.lap_history.personal_best_reference(&track.name));
+        self.lap_delta.use_all_time_best(reference);
+    }
+
+    /// Label the laps that follow with a car setup. Personal bests are kept
+    /// per setup, so an all-time-best reference is reloaded for the new one.
+    pub fn set_car_setup(&mut self, setup: Option<String>) {
+        self.lap_history.setup = setup;
+        if self.lap_delta.source() == ReferenceSource::AllTimeBest {
+            self.use_all_time_best();
+        }
+    }
+
+    /// Go back to selecting tracks from the GNSS position
+    pub fn enable_track_detection(&mut self) {
+        self.track_detection = true;
+        self.active_track = None;
+        self.pit_monitor = None;
+        self.clear_start_finish_line();
+    }
+
+    /// Feed a RaceBox sample to the lap timer and the live delta
+    fn update_timing(&mut self, data: &RaceBoxData) {
+        let now = self.now();
+        let Some(timer) = self.lap_timer.as_mut() else {
+            return;
+        };
+        match timer.update(data, now) {
+            Some(LapEvent::Started) => {
+                crate::telemetry_log!(log::Level::Info, "Lap timing started");
+                self.stint.on_lap_started();
+            }
+            Some(LapEvent::Split(split)) => log_split(&split),
+            Some(LapEvent::Completed(lap)) => {
+                if let Some(split) = timer.last_split() {
+                    log_split(split);
+                }
+                crate::telemetry_log!(log::Level::Info, "Lap {}: {}", lap.number, format_lap_time(lap.time));
+                self.lap_delta.on_lap_completed(&lap, timer);
+                if self.stint.on_lap_completed(&lap) {
+                    crate::telemetry_log!(
+                        log::Level::Warn,
+                        "Pit window open: {:.1} laps of fuel left",
+                        self.stint.laps_remaining().unwrap_or_default()
+                    );
+                }
+                // Laps timed in replays and exports were stored when they were driven
+                if matches!(self.clock, TelemetryClock::Live(_))
+                    && let Some(track) = &self.active_track
+                    && let Err(e) = self.lap_history.append(&track.name, &lap)
+                {
+                    crate::telemetry_log!(log::Level::Error, "Failed to store lap {}: {}", lap.number, e);
+                }
+            }
+            None => {}
+        }
+        self.lap_delta.update(timer);
+    }
+
+    /// Detect pit entry and exit, and keep in and out laps out of the best-lap figures
+    fn update_pit_monitor(&mut self, data: &RaceBoxData) {
+        let now = self.now();
+        let Some(monitor) = self.pit_monitor.as_mut() else {
+            return;
+        };
+        let was_speeding = monitor.speeding();
+        match monitor.update(data, now) {
+            Some(PitEvent::Entered) => crate::telemetry_log!(log::Level::Info, "Pit entry"),
+            Some(PitEvent::Exited(stop)) => {
+                crate::telemetry_log!(
+                    log::Level::Info,
+                    "Pit stop {}: {:.1} s in the pit lane, {:.1} s stationary",
+                    stop.number,
+                    stop.lane_time.as_secs_f32(),
+                    stop.stationary_time.as_secs_f32()
+                );
+                self.stint.new_stint(now);
+                self.tyres.reset(now);
+            }
+            None => {}
+        }
+        if monitor.speeding() && !was_speeding {
+            crate::telemetry_log!(
+                log::Level::Warn,
+                "Pit lane speed limit exceeded: {:.0} km/h (limit {:.0})",
+                data.speed_kph,
+                monitor.speed_limit_kph()
+            );
+        }
+        if monitor.in_pit_lane()
+            && let Some(timer) = self.lap_timer.as_mut()
+        {
+            timer.mark_pit_lap();
+        }
+    }
+
+    /// Alerts for the driver that currently apply, most urgent first
+    pub fn alerts(&self) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        if let Some(monitor) = &self.pit_monitor {
+            if monitor.speeding() {
+                let speed = self.latest_racebox_data.as_ref().map_or(0.0, |d| d.speed_kph);
+                alerts.push(Alert::new(
+                    AlertLevel::Critical,
+                    format!("PIT LIMIT {:.0} / {:.0} km/h", speed, monitor.speed_limit_kph()),
+                ));
+            } else if let Some(time) = monitor.time_in_lane(self.now()) {
+                alerts.push(Alert::new(AlertLevel::Info, format!("PIT LANE {:.0} s", time.as_secs_f32())));
+            }
+        }
+        let now = self.now();
+        if let Some(laps) = self.stint.laps_remaining() {
+            if laps < 1.0 {
+                alerts.push(Alert::new(AlertLevel::Critical, "BOX THIS LAP: FUEL"));
+            } else if self.stint.pit_window_open() {
+                alerts.push(Alert::new(AlertLevel::Warning, format!("PIT WINDOW OPEN {:.1} laps", laps)));
+            }
+        }
+        for (corner, name) in CORNERS.iter().enumerate() {
+            if self.tyres.leaking(corner) {
+                alerts.push(Alert::new(AlertLevel::Warning, format!("SLOW LEAK {}", name)));
+            }
+        }
+        if self.stint.over_max_time(now) {
+            alerts.push(Alert::new(
+                AlertLevel::Warning,
+                format!("STINT TIME {}", format_stint_time(self.stint.time(now))),
+            ));
+        }
+        alerts.sort_by_key(|alert| std::cmp::Reverse(alert.level));
+        alerts
+    }
+
+    /// Turn acceleration run timing on (with a fresh timer) or off
+    pub fn set_accel_timing(&mut self, enabled: bool) {
+        self.accel_timer = enabled.then(|| AccelTimer::new(self.performance.clone()));
+    }
+
+    fn update_accel_timer(&mut self, data: &RaceBoxData) {
+        let Some(timer) = self.accel_timer.as_mut() else {
+            return;
+        };
+        for event in timer.update(data) {
+            match event {
+                AccelEvent::Armed => crate::telemetry_log!(log::Level::Info, "Acceleration runs armed"),
+                AccelEvent::Launched => crate::telemetry_log!(log::Level::Info, "Launch detected"),
+                AccelEvent::Completed(result) => {
+                    crate::telemetry_log!(
+                        log::Level::Info,
+                        "{}: {:.2} s over {:.1} m (slope {:+.1}%)",
+                        result.name,
+                        result.time_s,
+                        result.distance_m,
+                        result.slope_percent
+                    );
+                    // Runs in replays and exports were stored when they were driven
+                    if matches!(self.clock, TelemetryClock::Live(_))
+                        && let Err(e) = performance::append_result(performance::results_path("accel_runs"), &result)
+                    {
+                        crate::telemetry_log!(log::Level::Error, "Failed to store {} run: {}", result.name, e);
+                    }
+                }
+                AccelEvent::Rejected { name, reason } => {
+                    crate::telemetry_log!(log::Level::Warn, "{} run rejected: {}", name, reason)
+                }
+            }
+        }
+    }
+
+    /// Turn braking test measurement on (with a fresh timer) or off
+    pub fn set_braking_tests(&mut self, enabled: bool) {
+        self.braking_timer = enabled.then(|| BrakingTimer::new(&self.performance));
+    }
+
+    fn update_braking_timer(&mut self, data: &RaceBoxData) {
+        let brake_bar = self.latest_esp32_data.brake_pressure_bar();
+        let Some(timer) = self.braking_timer.as_mut() else {
+            return;
+        };
+        for event in timer.update(data, brake_bar) {
+            match event {
+                BrakingEvent::Started(name) => crate::telemetry_log!(log::Level::Info, "{} braking test started", name),
+                BrakingEvent::Completed(result) => {
+                    crate::telemetry_log!(
+                        log::Level::Info,
+                        "{}: {:.1} m in {:.2} s, peak {:.2} g, mean {:.2} g (level ground {:.1} m)",
+                        result.name,
+                        result.distance_m,
+                        result.time_s,
+                        result.peak_decel_g,
+                        result.mean_decel_g,
+                        result.corrected_distance_m
+                    );
+                    // Stops in replays and exports were stored when they were driven
+                    if matches!(self.clock, TelemetryClock::Live(_))
+                        && let Err(e) = performance::append_result(performance::results_path("braking_tests"), &result)
+                    {
+                        crate::telemetry_log!(log::Level::Error, "Failed to store {} test: {}", result.name, e);
+                    }
+                }
+                BrakingEvent::Rejected { name, reason } => {
+                    crate::telemetry_log!(log::Level::Warn, "{} braking test rejected: {}", name, reason)
+                }
+            }
+        }
+    }
+
+    /// Road speed from the source chosen in the settings
+    pub fn speed(&self) -> Option<SpeedReading> {
+        let wheel = self.latest_esp32_data.speed.map(|kph| SpeedReading {
+            kph: kph as f32,
+            source: SpeedSource::Wheel,
+            gnss_quality: None,
+        });
+        let gnss = self.latest_racebox_data.as_ref().filter(|d| d.fix_ok).map(|d| SpeedReading {
+            kph: d.speed_kph,
+            source: SpeedSource::Gnss,
+            gnss_quality: Some(d.gnss_quality()),
+        });
+        match self.settings.speed_source {
+            SpeedSource::Wheel => wheel,
+            SpeedSource::Gnss => gnss,
+            SpeedSource::Fused => match gnss {
+                Some(reading) if reading.gnss_quality >= Some(GnssQuality::Fair) => Some(reading),
+                _ => wheel.or(gnss),
+            },
+        }
+    }
+
+    /// Change the settings and save them
+    pub fn update_settings(&mut self, change: impl FnOnce(&mut Settings)) {
+        change(&mut self.settings);
+        if let Err(e) = self.settings.save(Settings::default_path()) {
+            crate::telemetry_log!(log::Level::Error, "Failed to save settings: {}", e);
+        }
+    }
+
+    /// Fuel in the tank in litres, from the latest ESP32 reading and the vehicle's calibration
+    pub fn fuel_litres(&self) -> Option<f32> {
+        self.vehicle.fuel.litres(self.latest_esp32_data.fuel_level?)
+    }
+
+    /// Line to draw the track map from: the active track's outline, or else the
+    /// trace of the session's best lap, or of the last lap if all were in or out laps
+    pub fn map_outline(&self) -> Vec<GeoPoint> {
+        if let Some(track) = &self.active_track
+            && !track.outline.is_empty()
+        {
+            return track.outline.clone();
+        }
+        let lap = self.lap_timer.as_ref().and_then(|timer| timer.best_lap().or(timer.last_lap()));
+        lap.map(|lap| lap.trace.iter().map(|p| p.position).collect()).unwrap_or_default()
+    }
+
+    /// Apply an already decoded ESP32 sample
+    pub fn apply_esp32_data(&mut self, data: ESP32Data) {
+        self.latest_esp32_data = data;
+        if let Some(litres) = self.fuel_litres() {
+            self.stint.on_fuel(litres, self.now());
+        }
+        let esp32 = &self.latest_esp32_data;
+        self.inputs.push(InputSample {
+            time: self.now(),
+            throttle_pct: esp32.throttle_position.map(f32::from),
+            brake_bar: esp32.brake_pressure_bar(),
+            steering_deg: esp32.steering_angle_deg(),
+        });
+        let pressures = std::array::from_fn(|corner| self.latest_esp32_data.tyre_pressure_bar(corner));
+        for corner in self.tyres.on_pressures(pressures, self.now()) {
+            crate::telemetry_log!(
+                log::Level::Warn,
+                "Slow leak suspected at {}: down {:.2} bar on the other tyres",
+                CORNERS[corner],
+                self.tyres.drop_bar(corner).unwrap_or_default()
+            );
+        }
+        self.health.esp32.record_sample(self.now());
+        self.clear_esp32_error();
+    }
+
+    /// Flag sources that delivered data before but went quiet.
+    ///
+    /// Existing errors (e.g. a failed BLE connection) are kept, since they say more
+    /// than a timeout does. The flag is cleared by the next sample.
+    pub fn check_source_health(&mut self) {
+        let now = self.now();
+        if self.racebox_error.is_none() && let Some(age) = self.health.racebox.stale_for(now) {
+            crate::racebox_log!(log::Level::Warn, "No RaceBox data for {:.1} s", age.as_secs_f32());
+            self.set_racebox_error(format!("No data for {:.1} s", age.as_secs_f32()));
+        }
+        if self.esp32_error.is_none() && let Some(age) = self.health.esp32.stale_for(now) {
+            crate::esp32_log!(log::Level::Warn, "No ESP32 data for {:.1} s", age.as_secs_f32());
+            self.set_esp32_error(format!("No data for {:.1} s", age.as_secs_f32()));
+        }
+    }
+
+    /// Forget everything learned from incoming samples, e.g. before a replay
+    /// seeks backwards and re-feeds the session from the start.
+    pub fn reset_session(&mut self) {
+        self.latest_racebox_data = None;
+        self.latest_esp32_data = ESP32Data::default();
+        self.racebox_error = None;
+        self.esp32_error = None;
+        self.markers.clear();
+        if let Some(timer) = self.lap_timer.as_mut() {
+            timer.reset();
+        }
+        self.lap_delta.reset();
+        if self.lap_delta.source() == ReferenceSource::AllTimeBest {
+            self.use_all_time_best();
+        }
+        if let Some(monitor) = self.pit_monitor.as_mut() {
+            monitor.reset();
+        }
+        if let Some(timer) = self.accel_timer.as_mut() {
+            timer.reset();
+        }
+        if let Some(timer) = self.braking_timer.as_mut() {
+            timer.reset();
+        }
+        self.stint = StintManager::new(&self.vehicle, self.now());
+        self.tyres = TyreMonitor::new(&self.vehicle.tyres, self.now());
+        self.inputs.clear();
+        self.g_force.reset();
+        self.health = HealthMonitor::default();
+    }
+
+    /// Start timing laps across `line`, dropping any laps timed so far.
+    /// Split lines already configured are kept, and track detection is turned off.
+    pub fn set_start_finish_line(&mut self, line: TimingLine) {
+        self.track_detection = false;
+        self.active_track = None;
+        self.pit_monitor = None;
+        let splits = self.lap_timer.take().map(|timer| timer.splits().to_vec()).unwrap_or_default();
+        self.lap_timer = Some(LapTimer::new(line, splits));
+        self.lap_delta.reset();
+    }
+
+    /// Add a split line after the existing ones, dropping any laps timed so far
+    pub fn add_split_line(&mut self, line: TimingLine) -> Result<(), &'static str> {
+        let timer = self.lap_timer.take().ok_or("no start/finish line set")?;
+        let mut splits = timer.splits().to_vec();
+        splits.push(line);
+        self.lap_timer = Some(LapTimer::new(*timer.start_finish(), splits));
+        self.lap_delta.reset();
+        Ok(())
+    }
+
+    pub fn clear_split_lines(&mut self) {
+        if let Some(timer) = self.lap_timer.take() {
+            self.lap_timer = Some(LapTimer::new(*timer.start_finish(), Vec::new()));
+        }
+        self.lap_delta.reset();
+    }
+
+    pub fn clear_start_finish_line(&mut self) {
+        self.lap_timer = None;
+        self.lap_delta.reset();
+    }
+
+    /// Lap boundaries on the telemetry clock: start/finish crossings when the lap
+    /// timer has seen any, manual beacon markers otherwise
+    pub fn lap_markers(&self) -> Vec<Duration> {
+        match &self.lap_timer {
+            Some(timer) if !timer.crossings().is_empty() => timer.crossings().to_vec(),
+            _ => self.markers.clone(),
+        }
+    }
+
+    pub fn start_recording<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SessionError> {
+        self.stop_recording();
+        self.recorder = Some(SessionRecorder::start(path, self.now())?);
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            recorder.stop();
+        }
+    }
+
+    fn record(&mut self, source: RecordSource, payload: &[u8]) {
+        let now = self.now();
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(now, source, payload);
         }
     }
 
@@ -141,10 +876,31 @@ impl TelemetryState {
     pub fn get_color_scheme(&self) -> ColorScheme {
         self.color_scheme
     }
+
+    pub fn set_page(&mut self, page: DashboardPage) {
+        self.page = page;
+    }
+}
+
+fn log_split(split: &SplitResult) {
+    crate::telemetry_log!(
+        log::Level::Info,
+        "Lap {} sector {}: {} ({})",
+        split.lap,
+        split.sector + 1,
+        format_lap_time(split.time),
+        split.delta_s().map(format_delta).unwrap_or_else(|| "first".to_string())
+    );
 }
 
 pub type SharedTelemetryState = Arc<Mutex<TelemetryState>>;
 
+pub mod health;
+pub mod alerts;
+pub mod tyres;
+pub mod inputs;
+pub mod g_force;
+
 #[cfg(feature = "mock_telemetry")]
 pub mod mock;
 
@@ -0,0 +1,23 @@
+/// How urgently an alert needs the driver's attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertLevel {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// A message for the driver, shown in a banner for as long as its condition holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub level: AlertLevel,
+    pub message: String,
+}
+
+impl Alert {
+    pub fn new(level: AlertLevel, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+        }
+    }
+}
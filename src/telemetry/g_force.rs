@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+/// How far back the G-force meter's trail goes
+pub const TRAIL_SPAN: Duration = Duration::from_secs(2);
+/// Directions of the peak envelope, evenly spaced around the circle
+const ENVELOPE_SECTORS: usize = 72;
+
+/// Lateral and longitudinal G from one RaceBox sample, in the RaceBox's axes
+#[derive(Debug, Clone, Copy)]
+pub struct GForceSample {
+    /// Telemetry clock time of the sample
+    pub time: Duration,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Recent G-force samples for the meter's trail, and the most G reached in
+/// every direction this session for its peak envelope (the "GG diagram").
+///
+/// Peaks per direction follow the meter's labels: front is negative Y, right is
+/// positive X, rear is positive Y and left is negative X.
+#[derive(Debug, Clone)]
+pub struct GForceHistory {
+    trail: VecDeque<GForceSample>,
+    /// Largest combined G seen in each direction sector, counter-clockwise from +X
+    envelope: [f32; ENVELOPE_SECTORS],
+    /// Largest G towards the front, right, rear and left
+    peaks: [f32; 4],
+}
+
+impl Default for GForceHistory {
+    fn default() -> Self {
+        Self {
+            trail: VecDeque::new(),
+            envelope: [0.0; ENVELOPE_SECTORS],
+            peaks: [0.0; 4],
+        }
+    }
+}
+
+impl GForceHistory {
+    pub fn push(&mut self, sample: GForceSample) {
+        // The clock went back, e.g. a replay seek, so the trail is from another time
+        if self.trail.back().is_some_and(|last| sample.time < last.time) {
+            self.trail.clear();
+        }
+        while self.trail.front().is_some_and(|s| sample.time.saturating_sub(s.time) > TRAIL_SPAN) {
+            self.trail.pop_front();
+        }
+        self.trail.push_back(sample);
+
+        let magnitude = sample.x.hypot(sample.y);
+        let angle = sample.y.atan2(sample.x).rem_euclid(2.0 * PI);
+        let sector = ((angle / (2.0 * PI) * ENVELOPE_SECTORS as f32) as usize).min(ENVELOPE_SECTORS - 1);
+        self.envelope[sector] = self.envelope[sector].max(magnitude);
+        for (peak, g) in self.peaks.iter_mut().zip([-sample.y, sample.x, sample.y, -sample.x]) {
+            *peak = peak.max(g);
+        }
+    }
+
+    /// Forget the trail and the peaks
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Samples from the last `TRAIL_SPAN`, oldest first
+    pub fn trail(&self) -> &VecDeque<GForceSample> {
+        &self.trail
+    }
+
+    /// The peak envelope as (x, y) points in G, one per direction reached so far,
+    /// counter-clockwise from +X
+    pub fn envelope(&self) -> Vec<(f32, f32)> {
+        self.envelope
+            .iter()
+            .enumerate()
+            .filter(|(_, magnitude)| **magnitude > 0.0)
+            .map(|(sector, magnitude)| {
+                let angle = (sector as f32 + 0.5) / ENVELOPE_SECTORS as f32 * 2.0 * PI;
+                (angle.cos() * magnitude, angle.sin() * magnitude)
+            })
+            .collect()
+    }
+
+    /// Largest G towards the front, right, rear and left
+    pub fn peaks(&self) -> [f32; 4] {
+        self.peaks
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::telemetry::SharedTelemetryState;
+
+/// Span of recent arrivals used for the rate and jitter figures
+const RATE_WINDOW: Duration = Duration::from_secs(2);
+/// A source that has delivered data before is reported stale after this long without a sample
+const STALE_AFTER: Duration = Duration::from_secs(1);
+/// How often the background task checks for stale sources
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+/// Weight of the newest sample in the latency moving average
+const LATENCY_SMOOTHING: f32 = 0.1;
+
+/// Delivery statistics for one telemetry source (RaceBox or ESP32).
+///
+/// Arrival times are taken from the telemetry clock so rates and jitter come out
+/// the same in replay. Latency to screen is wall-clock time between a sample
+/// being applied and the first frame rendered after it.
+#[derive(Debug, Clone, Default)]
+pub struct SourceHealth {
+    arrivals: VecDeque<Duration>,
+    last_arrival: Option<Duration>,
+    pending_render: Option<Instant>,
+    latency_ms: Option<f32>,
+    pub samples: u64,
+    pub parse_failures: u64,
+}
+
+impl SourceHealth {
+    /// Note a successfully decoded sample
+    pub fn record_sample(&mut self, now: Duration) {
+        self.samples += 1;
+        self.last_arrival = Some(now);
+        self.arrivals.push_back(now);
+        while self.arrivals.front().is_some_and(|&t| now.saturating_sub(t) > RATE_WINDOW) {
+            self.arrivals.pop_front();
+        }
+        self.pending_render.get_or_insert_with(Instant::now);
+    }
+
+    /// Note a packet or frame that could not be decoded
+    pub fn record_parse_failure(&mut self) {
+        self.parse_failures += 1;
+    }
+
+    /// Note that a frame showing the latest sample reached the screen
+    pub fn record_render(&mut self, rendered_at: Instant) {
+        if let Some(applied) = self.pending_render.take() {
+            let latency = rendered_at.saturating_duration_since(applied).as_secs_f32() * 1000.0;
+            self.latency_ms = Some(match self.latency_ms {
+                Some(avg) => avg + (latency - avg) * LATENCY_SMOOTHING,
+                None => latency,
+            });
+        }
+    }
+
+    /// Samples per second over the last couple of seconds
+    pub fn rate_hz(&self) -> Option<f32> {
+        let first = self.arrivals.front()?;
+        let last = self.arrivals.back()?;
+        let span = last.saturating_sub(*first).as_secs_f32();
+        (span > 0.0).then(|| (self.arrivals.len() - 1) as f32 / span)
+    }
+
+    /// Standard deviation of the inter-arrival time, in milliseconds
+    pub fn jitter_ms(&self) -> Option<f32> {
+        if self.arrivals.len() < 3 {
+            return None;
+        }
+        let intervals: Vec<f32> = self
+            .arrivals
+            .iter()
+            .zip(self.arrivals.iter().skip(1))
+            .map(|(a, b)| b.saturating_sub(*a).as_secs_f32() * 1000.0)
+            .collect();
+        let mean = intervals.iter().sum::<f32>() / intervals.len() as f32;
+        let variance = intervals.iter().map(|i| (i - mean).powi(2)).sum::<f32>() / intervals.len() as f32;
+        Some(variance.sqrt())
+    }
+
+    /// Time since the last sample, if one was ever received
+    pub fn age(&self, now: Duration) -> Option<Duration> {
+        self.last_arrival.map(|t| now.saturating_sub(t))
+    }
+
+    /// Smoothed latency from sample to screen, in milliseconds
+    pub fn latency_ms(&self) -> Option<f32> {
+        self.latency_ms
+    }
+
+    /// How long the source has been quiet, if it delivered data before and is now stale
+    pub fn stale_for(&self, now: Duration) -> Option<Duration> {
+        self.age(now).filter(|age| *age > STALE_AFTER)
+    }
+
+    /// One-line summary, as reported by the `health` command
+    pub fn summary(&self, now: Duration) -> String {
+        let fmt_ms = |v: Option<f32>| v.map(|v| format!("{:.1}ms", v)).unwrap_or_else(|| "-".to_string());
+        format!(
+            "rate={} jitter={} age={} latency={} samples={} failures={}",
+            self.rate_hz().map(|r| format!("{:.1}Hz", r)).unwrap_or_else(|| "-".to_string()),
+            fmt_ms(self.jitter_ms()),
+            fmt_ms(self.age(now).map(|a| a.as_secs_f32() * 1000.0)),
+            fmt_ms(self.latency_ms),
+            self.samples,
+            self.parse_failures,
+        )
+    }
+}
+
+/// Health of every telemetry source
+#[derive(Debug, Clone, Default)]
+pub struct HealthMonitor {
+    pub racebox: SourceHealth,
+    pub esp32: SourceHealth,
+}
+
+impl HealthMonitor {
+    pub fn record_render(&mut self, rendered_at: Instant) {
+        self.racebox.record_render(rendered_at);
+        self.esp32.record_render(rendered_at);
+    }
+}
+
+/// Periodically flag sources that stopped delivering data
+pub async fn start_health_monitor(telemetry_state: SharedTelemetryState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            telemetry_state.lock().await.check_source_health();
+        }
+    });
+}
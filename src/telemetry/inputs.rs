@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How much driver input history is kept, and so the longest trace that can be shown
+pub const HISTORY_SPAN: Duration = Duration::from_secs(60);
+
+/// Driver inputs from one ESP32 sample
+#[derive(Debug, Clone, Copy)]
+pub struct InputSample {
+    /// Telemetry clock time of the sample
+    pub time: Duration,
+    pub throttle_pct: Option<f32>,
+    pub brake_bar: Option<f32>,
+    /// Positive to the right
+    pub steering_deg: Option<f32>,
+}
+
+/// The last minute of throttle, brake and steering, for the input trace.
+///
+/// Freezing keeps a copy of the history as it was, so the trace can be studied
+/// while recording carries on underneath.
+#[derive(Debug, Clone, Default)]
+pub struct InputHistory {
+    samples: VecDeque<InputSample>,
+    /// Time of the freeze and the history up to it
+    frozen: Option<(Duration, VecDeque<InputSample>)>,
+}
+
+impl InputHistory {
+    pub fn push(&mut self, sample: InputSample) {
+        while self.samples.front().is_some_and(|s| sample.time.saturating_sub(s.time) > HISTORY_SPAN) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.frozen = None;
+    }
+
+    pub fn freeze(&mut self, now: Duration) {
+        self.frozen = Some((now, self.samples.clone()));
+    }
+
+    pub fn resume(&mut self) {
+        self.frozen = None;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    /// The end of the shown history and the samples up to it: `now` and the live
+    /// samples, or the time and samples of the freeze
+    pub fn view(&self, now: Duration) -> (Duration, &VecDeque<InputSample>) {
+        match &self.frozen {
+            Some((at, samples)) => (*at, samples),
+            None => (now, &self.samples),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::time::sleep;
-use crate::telemetry::{SharedTelemetryState, TelemetryError, ESP32Data};
+use crate::telemetry::{SharedTelemetryState, TelemetryError, ESP32Data, StatusFlags};
 use crate::racebox::parser::RaceBoxData;
 use rand::rngs::SmallRng;
 use rand::{SeedableRng, Rng};
@@ -57,20 +57,27 @@ pub async fn start_mock_telemetry(telemetry_state: SharedTelemetryState) {
                 boost_pressure: Some((500.0 + ((t * 0.3).sin() * 700.0)) as u16),
                 rpm: Some(2000 + ((t * 1.5).sin() * 1500.0) as u16),
                 speed: Some(speed_kph as u16),
-                status_flags: None,
+                // Indicating left for 5 s out of every 20, with the flasher at 1.5 Hz
+                status_flags: Some(StatusFlags {
+                    left_turn: t % 20.0 < 5.0 && (t * 3.0) as u32 % 2 == 0,
+                    high_beam: t % 30.0 > 20.0,
+                    ..StatusFlags::default()
+                }),
                 steering_angle: Some(((t * 0.5).sin() * 300.0) as i16),
-                brake_pressure: Some(1000 + ((t * 0.7).cos() * 500.0) as u16),
-                throttle_position: Some((50.0 + (t * 0.8).sin() * 40.0) as u8),
+                // On the brakes whenever the throttle is closed
+                brake_pressure: Some(((-(t * 0.8).sin() - 0.4).max(0.0) * 6000.0) as u16),
+                throttle_position: Some((40.0 + (t * 0.8).sin() * 60.0).clamp(0.0, 100.0) as u8),
                 gear_position: Some(3 + ((t * 0.2).sin() * 2.0) as u8),
-                tyre_pressures: [Some(2200), Some(2200), Some(2100), Some(2100)],
-                tyre_temps: [Some(300), Some(305), Some(295), Some(290)],
+                tyre_pressures: [Some(220), Some(220), Some(210), Some(210)],
+                tyre_temps: [Some(780), Some(805), Some(845), Some(870)],
+                coolant_temp: Some((880.0 + (t * 0.05).sin() * 150.0) as i16),
+                oil_temp: Some((980.0 + (t * 0.04).sin() * 200.0) as i16),
             };
 
             {
                 let mut state = telemetry_state.lock().await;
-                state.latest_racebox_data = Some(racebox_data);
-                state.latest_esp32_data = esp32_data;
-                state.racebox_error = None;
+                state.apply_racebox_data(racebox_data);
+                state.apply_esp32_data(esp32_data);
                 state.esp32_error = None;
             }
 
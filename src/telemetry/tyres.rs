@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use crate::vehicle::TyreConfig;
+
+/// Tyre corners in the order the ESP32 sends them
+pub const CORNERS: [&str; 4] = ["FL", "FR", "RL", "RR"];
+/// Time constant of the smoothing applied to each tyre's pressure offset
+const PRESSURE_SMOOTHING_S: f32 = 30.0;
+/// The offsets are taken as the reference once they have been smoothed this long
+const BASELINE_AFTER: Duration = Duration::from_secs(60);
+
+/// Watches the tyre pressures for a slow leak.
+///
+/// Every tyre's pressure rises and falls with temperature, so a leak shows up in
+/// how a tyre compares to the others rather than in its own pressure. Each tyre's
+/// offset from the mean of the other three is smoothed and, once settled, kept as
+/// the reference; a tyre whose offset then falls `leak_drop_bar` below its
+/// reference is flagged. The reference is taken again after a pit stop, since
+/// pressures may have been adjusted.
+#[derive(Debug, Clone)]
+pub struct TyreMonitor {
+    leak_drop_bar: f32,
+    started_at: Duration,
+    last_at: Option<Duration>,
+    /// Smoothed pressure less the mean of the other tyres, per corner
+    offsets: [Option<f32>; 4],
+    baseline: Option<[Option<f32>; 4]>,
+    leaking: [bool; 4],
+}
+
+impl TyreMonitor {
+    pub fn new(config: &TyreConfig, now: Duration) -> Self {
+        Self {
+            leak_drop_bar: config.leak_drop_bar,
+            started_at: now,
+            last_at: None,
+            offsets: [None; 4],
+            baseline: None,
+            leaking: [false; 4],
+        }
+    }
+
+    /// Forget the reference and take it again from the pressures from `now` on
+    pub fn reset(&mut self, now: Duration) {
+        self.started_at = now;
+        self.last_at = None;
+        self.offsets = [None; 4];
+        self.baseline = None;
+        self.leaking = [false; 4];
+    }
+
+    /// Feed the tyre pressures in bar received at `now`. Returns the corners newly
+    /// found to be leaking.
+    pub fn on_pressures(&mut self, pressures: [Option<f32>; 4], now: Duration) -> Vec<usize> {
+        let dt = self.last_at.map_or(0.0, |last| now.saturating_sub(last).as_secs_f32());
+        self.last_at = Some(now);
+        let alpha = 1.0 - (-dt / PRESSURE_SMOOTHING_S).exp();
+        for corner in 0..4 {
+            // Comparing against fewer than two other tyres says too little
+            let others: Vec<f32> = (0..4).filter(|&i| i != corner).filter_map(|i| pressures[i]).collect();
+            let offset = match pressures[corner] {
+                Some(pressure) if others.len() >= 2 => pressure - others.iter().sum::<f32>() / others.len() as f32,
+                _ => continue,
+            };
+            self.offsets[corner] = Some(match self.offsets[corner] {
+                Some(smoothed) => smoothed + (offset - smoothed) * alpha,
+                None => offset,
+            });
+        }
+
+        if self.baseline.is_none() && now.saturating_sub(self.started_at) >= BASELINE_AFTER {
+            self.baseline = Some(self.offsets);
+        }
+        let Some(baseline) = self.baseline else {
+            return Vec::new();
+        };
+        let mut new_leaks = Vec::new();
+        for corner in 0..4 {
+            let drop = baseline[corner].zip(self.offsets[corner]).map(|(baseline, offset)| baseline - offset);
+            let leaking = drop.is_some_and(|drop| drop >= self.leak_drop_bar);
+            if leaking && !self.leaking[corner] {
+                new_leaks.push(corner);
+            }
+            self.leaking[corner] = leaking;
+        }
+        new_leaks
+    }
+
+    pub fn leaking(&self, corner: usize) -> bool {
+        self.leaking[corner]
+    }
+
+    /// How far the tyre's pressure has fallen relative to the others since the
+    /// reference was taken, in bar
+    pub fn drop_bar(&self, corner: usize) -> Option<f32> {
+        Some(self.baseline?[corner]? - self.offsets[corner]?)
+    }
+}
@@ -0,0 +1,6 @@
+pub mod geo;
+pub mod lap_timer;
+pub mod delta;
+pub mod history;
+pub mod pit;
+pub mod stint;
@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::timing::geo::GeoPoint;
+use crate::timing::lap_timer::{Lap, LapTimer, TracePoint};
+
+/// Span of delta history the trend is computed over, in seconds
+const TREND_WINDOW_S: f64 = 1.0;
+
+#[derive(Error, Debug)]
+pub enum DeltaError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid reference lap file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Reference lap has no trace")]
+    EmptyTrace,
+
+    #[error("Reference lap time must be a positive number of seconds")]
+    InvalidLapTime,
+
+    #[error("Reference lap trace must run forward in time and distance")]
+    UnsortedTrace,
+}
+
+/// A lap to compare against, indexed by distance from the start/finish line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceLap {
+    pub lap_time_s: f64,
+    pub trace: Vec<TracePoint>,
+}
+
+impl ReferenceLap {
+    pub fn from_lap(lap: &Lap) -> Self {
+        Self {
+            lap_time_s: lap.time.as_secs_f64(),
+            trace: lap.trace.clone(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DeltaError> {
+        let reference: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        reference.validate()?;
+        Ok(reference)
+    }
+
+    /// Check a reference read back from disk: the lookups by distance and time
+    /// need a trace sorted by both, and the lap time must convert to a `Duration`
+    pub fn validate(&self) -> Result<(), DeltaError> {
+        if self.trace.is_empty() {
+            return Err(DeltaError::EmptyTrace);
+        }
+        if !(self.lap_time_s.is_finite() && self.lap_time_s > 0.0) {
+            return Err(DeltaError::InvalidLapTime);
+        }
+        let finite = self.trace.iter().all(|p| p.time_s.is_finite() && p.distance_m.is_finite());
+        let sorted = self
+            .trace
+            .windows(2)
+            .all(|pair| pair[0].time_s <= pair[1].time_s && pair[0].distance_m <= pair[1].distance_m);
+        if !(finite && sorted) {
+            return Err(DeltaError::UnsortedTrace);
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), DeltaError> {
+        serde_json::to_writer(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+
+    pub fn lap_time(&self) -> Duration {
+        Duration::from_secs_f64(self.lap_time_s)
+    }
+
+    /// Time the reference lap took to cover `distance_m`, interpolated between
+    /// trace points and held at the end of the trace
+    fn time_at_distance(&self, distance_m: f64) -> Option<f64> {
+        let after = self.trace.partition_point(|p| p.distance_m < distance_m);
+        if after == 0 {
+            return self.trace.first().map(|p| p.time_s);
+        }
+        let Some(b) = self.trace.get(after) else {
+            return self.trace.last().map(|p| p.time_s);
+        };
+        let a = &self.trace[after - 1];
+        let span = b.distance_m - a.distance_m;
+        let t = if span > 0.0 { (distance_m - a.distance_m) / span } else { 0.0 };
+        Some(a.time_s + (b.time_s - a.time_s) * t)
+    }
+
+    /// Where the reference lap was `time_s` into the lap, interpolated between
+    /// trace points and held at the end of the trace
+    pub fn position_at_time(&self, time_s: f64) -> Option<GeoPoint> {
+        let after = self.trace.partition_point(|p| p.time_s < time_s);
+        if after == 0 {
+            return self.trace.first().map(|p| p.position);
+        }
+        let Some(b) = self.trace.get(after) else {
+            return self.trace.last().map(|p| p.position);
+        };
+        let a = &self.trace[after - 1];
+        let span = b.time_s - a.time_s;
+        let t = if span > 0.0 { (time_s - a.time_s) / span } else { 0.0 };
+        Some(GeoPoint::new(
+            a.position.lat + (b.position.lat - a.position.lat) * t,
+            a.position.lon + (b.position.lon - a.position.lon) * t,
+        ))
+    }
+}
+
+/// Which lap the delta is computed against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceSource {
+    /// Fastest lap of this session, replaced whenever it is beaten
+    SessionBest,
+    /// A lap loaded from a file, kept until another reference is chosen
+    Loaded,
+    /// Personal best at the current track from the lap history, replaced when beaten
+    AllTimeBest,
+}
+
+/// Live time difference to a reference lap.
+///
+/// Updated on every RaceBox fix (25 Hz): the current lap's elapsed time is
+/// compared with the time the reference lap took to cover the same distance.
+#[derive(Debug, Clone)]
+pub struct LapDelta {
+    source: ReferenceSource,
+    reference: Option<ReferenceLap>,
+    /// Seconds behind (positive) or ahead (negative) of the reference
+    delta_s: Option<f64>,
+    /// (lap time, delta) pairs over the trend window
+    history: VecDeque<(f64, f64)>,
+}
+
+impl Default for LapDelta {
+    fn default() -> Self {
+        Self {
+            source: ReferenceSource::SessionBest,
+            reference: None,
+            delta_s: None,
+            history: VecDeque::new(),
+        }
+    }
+}
+
+impl LapDelta {
+    pub fn source(&self) -> ReferenceSource {
+        self.source
+    }
+
+    pub fn reference(&self) -> Option<&ReferenceLap> {
+        self.reference.as_ref()
+    }
+
+    /// Compare against the session's best lap from now on
+    pub fn use_session_best(&mut self, timer: Option<&LapTimer>) {
+        self.source = ReferenceSource::SessionBest;
+        self.reference = timer.and_then(|t| t.best_lap()).map(ReferenceLap::from_lap);
+        self.clear_live();
+    }
+
+    /// Compare against the stored personal best (if there is one yet)
+    pub fn use_all_time_best(&mut self, reference: Option<ReferenceLap>) {
+        self.source = ReferenceSource::AllTimeBest;
+        self.reference = reference;
+        self.clear_live();
+    }
+
+    /// Compare against a lap loaded from elsewhere
+    pub fn use_reference(&mut self, reference: ReferenceLap) {
+        self.source = ReferenceSource::Loaded;
+        self.reference = Some(reference);
+        self.clear_live();
+    }
+
+    /// Forget the live figures, and the reference unless it was loaded from a file
+    pub fn reset(&mut self) {
+        if self.source != ReferenceSource::Loaded {
+            self.reference = None;
+        }
+        self.clear_live();
+    }
+
+    fn clear_live(&mut self) {
+        self.delta_s = None;
+        self.history.clear();
+    }
+
+    /// Called when a lap completes, before the next `update`
+    pub fn on_lap_completed(&mut self, lap: &Lap, timer: &LapTimer) {
+        let replace = match self.source {
+            ReferenceSource::SessionBest => timer.best_lap().is_some_and(|best| best.number == lap.number),
+            ReferenceSource::AllTimeBest => self
+                .reference
+                .as_ref()
+                .is_none_or(|reference| lap.time.as_secs_f64() < reference.lap_time_s),
+            ReferenceSource::Loaded => false,
+        };
+        if replace && !lap.pit && !lap.trace.is_empty() {
+            self.reference = Some(ReferenceLap::from_lap(lap));
+        }
+        self.clear_live();
+    }
+
+    /// Recompute the delta from the lap in progress
+    pub fn update(&mut self, timer: &LapTimer) {
+        let (Some(reference), Some(current)) = (&self.reference, timer.current_trace().last()) else {
+            self.clear_live();
+            return;
+        };
+        let Some(reference_time) = reference.time_at_distance(current.distance_m) else {
+            return;
+        };
+        let delta = current.time_s - reference_time;
+        self.delta_s = Some(delta);
+        self.history.push_back((current.time_s, delta));
+        while self.history.front().is_some_and(|(t, _)| current.time_s - t > TREND_WINDOW_S) {
+            self.history.pop_front();
+        }
+    }
+
+    /// Seconds behind (positive) or ahead (negative) of the reference
+    pub fn delta_s(&self) -> Option<f64> {
+        self.delta_s
+    }
+
+    /// Expected time for the lap in progress if the current delta holds to the line
+    pub fn predicted_lap_time(&self) -> Option<Duration> {
+        let lap_time = self.reference.as_ref()?.lap_time_s + self.delta_s?;
+        Some(Duration::from_secs_f64(lap_time.max(0.0)))
+    }
+
+    /// How fast the delta is changing, in seconds per second of lap time.
+    /// Negative means time is being gained.
+    pub fn trend(&self) -> Option<f64> {
+        let (t0, d0) = self.history.front()?;
+        let (t1, d1) = self.history.back()?;
+        (t1 > t0).then(|| (d1 - d0) / (t1 - t0))
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+/// Mean Earth radius used for distances and the local projection
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// A WGS84 position in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+
+    /// Great-circle distance in metres
+    pub fn distance_m(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.lon - self.lon).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+    }
+}
+
+/// A point in a local tangent plane, in metres east and north of the origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl LocalPoint {
+    pub fn distance(&self, other: &LocalPoint) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+
+    /// Point at fraction `t` of the way from `self` to `other`
+    pub fn lerp(&self, other: &LocalPoint, t: f64) -> LocalPoint {
+        LocalPoint {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+        }
+    }
+}
+
+/// Equirectangular projection around a fixed origin.
+///
+/// The error is a few centimetres per kilometre from the origin, which is fine
+/// across the extent of a circuit.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalFrame {
+    origin: GeoPoint,
+    cos_lat: f64,
+}
+
+impl LocalFrame {
+    pub fn new(origin: GeoPoint) -> Self {
+        Self {
+            origin,
+            cos_lat: origin.lat.to_radians().cos(),
+        }
+    }
+
+    pub fn to_local(self, point: GeoPoint) -> LocalPoint {
+        LocalPoint {
+            x: (point.lon - self.origin.lon).to_radians() * EARTH_RADIUS_M * self.cos_lat,
+            y: (point.lat - self.origin.lat).to_radians() * EARTH_RADIUS_M,
+        }
+    }
+
+    pub fn to_geo(self, point: LocalPoint) -> GeoPoint {
+        GeoPoint {
+            lat: self.origin.lat + (point.y / EARTH_RADIUS_M).to_degrees(),
+            lon: self.origin.lon + (point.x / (EARTH_RADIUS_M * self.cos_lat)).to_degrees(),
+        }
+    }
+}
+
+/// Intersection of segments `p1→p2` and `q1→q2`.
+///
+/// Returns the fraction along `p1→p2` where they cross, or `None` if they don't
+/// (parallel segments never cross).
+pub fn segment_intersection(p1: LocalPoint, p2: LocalPoint, q1: LocalPoint, q2: LocalPoint) -> Option<f64> {
+    let r = (p2.x - p1.x, p2.y - p1.y);
+    let s = (q2.x - q1.x, q2.y - q1.y);
+    let denom = r.0 * s.1 - r.1 * s.0;
+    if denom.abs() < f64::EPSILON {
+        return None;
+    }
+    let qp = (q1.x - p1.x, q1.y - p1.y);
+    let t = (qp.0 * s.1 - qp.1 * s.0) / denom;
+    let u = (qp.0 * r.1 - qp.1 * r.0) / denom;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t)
+}
+
+/// Whether `point` is inside `polygon` (even-odd rule, so the polygon may be
+/// given in either winding order)
+pub fn point_in_polygon(point: LocalPoint, polygon: &[LocalPoint]) -> bool {
+    let mut inside = false;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Fraction along `p1→p2` where it first crosses the edge of `polygon`, if it does
+pub fn polygon_crossing(p1: LocalPoint, p2: LocalPoint, polygon: &[LocalPoint]) -> Option<f64> {
+    polygon
+        .iter()
+        .enumerate()
+        .filter_map(|(i, a)| segment_intersection(p1, p2, *a, polygon[(i + 1) % polygon.len()]))
+        .min_by(f64::total_cmp)
+}
+
+/// Smallest absolute difference between two compass headings, in degrees (0–180)
+pub fn heading_difference(a: f32, b: f32) -> f32 {
+    let diff = (a - b).rem_euclid(360.0);
+    diff.min(360.0 - diff)
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::timing::delta::ReferenceLap;
+use crate::timing::lap_timer::{Lap, TracePoint};
+
+const HISTORY_DIR: &str = "data";
+const HISTORY_FILE: &str = "laps.jsonl";
+/// Trace points closer together than this are dropped before a lap is stored
+const TRACE_SPACING_M: f64 = 5.0;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid lap record: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Everything stored about a lap, one JSON object per line of `data/laps.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LapRecord {
+    #[serde(flatten)]
+    pub summary: LapSummary,
+    /// Trace thinned to a point every few metres, used as a reference lap
+    pub trace: Vec<TracePoint>,
+}
+
+/// A stored lap without its trace, as kept in memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LapSummary {
+    pub track: String,
+    /// Free-form car setup label, e.g. "soft springs"
+    pub setup: Option<String>,
+    /// Free-form conditions label, e.g. "damp"
+    pub conditions: Option<String>,
+    /// Wall clock start of the dashboard run the lap belongs to, in ms since the Unix epoch
+    pub session_unix_ms: u64,
+    /// Wall clock time the lap was completed, in ms since the Unix epoch
+    pub recorded_unix_ms: u64,
+    pub lap: u32,
+    pub lap_time_s: f64,
+    pub sectors_s: Vec<Option<f64>>,
+    pub max_speed_kph: f32,
+    /// In or out lap, left out of personal bests
+    #[serde(default)]
+    pub pit: bool,
+}
+
+impl LapSummary {
+    pub fn lap_time(&self) -> Duration {
+        Duration::from_secs_f64(self.lap_time_s)
+    }
+}
+
+/// A dashboard run at one track, summarised
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub track: String,
+    pub session_unix_ms: u64,
+    pub laps: usize,
+    /// Fastest lap other than in and out laps
+    pub best_lap: Option<Duration>,
+}
+
+/// Append-only store of every completed lap.
+///
+/// Summaries are loaded into memory at startup, along with the trace of the
+/// personal best for each track and setup, so picking a reference lap never
+/// reads the file again. Other traces stay on disk.
+pub struct LapHistory {
+    path: PathBuf,
+    session_unix_ms: u64,
+    summaries: Vec<LapSummary>,
+    /// Personal best at each track (lowercased) and setup, as a reference lap
+    best_references: BTreeMap<(String, Option<String>), ReferenceLap>,
+    pub setup: Option<String>,
+    pub conditions: Option<String>,
+}
+
+impl LapHistory {
+    pub fn default_path() -> PathBuf {
+        Path::new(HISTORY_DIR).join(HISTORY_FILE)
+    }
+
+    /// Open the store at `path`, skipping any line that can't be parsed
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path);
+        let mut history = Self {
+            path,
+            session_unix_ms: unix_ms_now(),
+            summaries: Vec::new(),
+            best_references: BTreeMap::new(),
+            setup: None,
+            conditions: None,
+        };
+        if let Ok(file) = file {
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if let Ok(record) = serde_json::from_str(&line) {
+                    history.add_record(record);
+                }
+            }
+        }
+        history
+    }
+
+    /// Keep `record`'s summary, and its trace if it is a new personal best
+    fn add_record(&mut self, record: LapRecord) {
+        let summary = record.summary;
+        let reference = ReferenceLap {
+            lap_time_s: summary.lap_time_s,
+            trace: record.trace,
+        };
+        if !summary.pit && reference.validate().is_ok() {
+            let key = (summary.track.to_ascii_lowercase(), summary.setup.clone());
+            let best = self.best_references.get(&key);
+            if best.is_none_or(|best| reference.lap_time_s < best.lap_time_s) {
+                self.best_references.insert(key, reference);
+            }
+        }
+        self.summaries.push(summary);
+    }
+
+    /// Append a completed lap at `track`
+    pub fn append(&mut self, track: &str, lap: &Lap) -> Result<(), HistoryError> {
+        let mut trace: Vec<TracePoint> = Vec::new();
+        for point in &lap.trace {
+            if trace.last().is_none_or(|last| point.distance_m - last.distance_m >= TRACE_SPACING_M) {
+                trace.push(*point);
+            }
+        }
+        // Always keep the closing point on the line
+        if let Some(last) = lap.trace.last()
+            && trace.last() != Some(last)
+        {
+            trace.push(*last);
+        }
+
+        let record = LapRecord {
+            summary: LapSummary {
+                track: track.to_string(),
+                setup: self.setup.clone(),
+                conditions: self.conditions.clone(),
+                session_unix_ms: self.session_unix_ms,
+                recorded_unix_ms: unix_ms_now(),
+                lap: lap.number,
+                lap_time_s: lap.time.as_secs_f64(),
+                sectors_s: lap.sectors.iter().map(|s| s.map(|s| s.as_secs_f64())).collect(),
+                max_speed_kph: lap.max_speed_kph,
+                pit: lap.pit,
+            },
+            trace,
+        };
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        OpenOptions::new().create(true).append(true).open(&self.path)?.write_all(&line)?;
+        self.add_record(record);
+        Ok(())
+    }
+
+    /// Fastest stored lap at `track` for each setup, leaving out in and out laps
+    pub fn personal_bests(&self, track: &str) -> BTreeMap<Option<String>, &LapSummary> {
+        let mut bests: BTreeMap<Option<String>, &LapSummary> = BTreeMap::new();
+        for lap in self.summaries.iter().filter(|l| !l.pit && l.track.eq_ignore_ascii_case(track)) {
+            let best = bests.entry(lap.setup.clone()).or_insert(lap);
+            if lap.lap_time_s < best.lap_time_s {
+                *best = lap;
+            }
+        }
+        bests
+    }
+
+    /// Fastest stored time for each of `count` sectors at `track`, in seconds, over
+    /// every setup. Laps timed with a different number of sectors are left out.
+    pub fn best_sectors(&self, track: &str, count: usize) -> Vec<Option<f64>> {
+        let mut bests = vec![None; count];
+        for lap in self
+            .summaries
+            .iter()
+            .filter(|l| !l.pit && l.track.eq_ignore_ascii_case(track) && l.sectors_s.len() == count)
+        {
+            for (best, time) in bests.iter_mut().zip(&lap.sectors_s) {
+                if let Some(time) = *time {
+                    *best = Some(best.map_or(time, |b: f64| b.min(time)));
+                }
+            }
+        }
+        bests
+    }
+
+    /// The most recent dashboard runs, newest first, one entry per run and track
+    pub fn recent_sessions(&self, limit: usize) -> Vec<SessionSummary> {
+        let mut sessions: Vec<SessionSummary> = Vec::new();
+        for lap in &self.summaries {
+            let time = (!lap.pit).then(|| lap.lap_time());
+            match sessions
+                .iter_mut()
+                .find(|s| s.session_unix_ms == lap.session_unix_ms && s.track == lap.track)
+            {
+                Some(session) => {
+                    session.laps += 1;
+                    session.best_lap = match (session.best_lap, time) {
+                        (Some(best), Some(time)) => Some(best.min(time)),
+                        (best, time) => best.or(time),
+                    };
+                }
+                None => sessions.push(SessionSummary {
+                    track: lap.track.clone(),
+                    session_unix_ms: lap.session_unix_ms,
+                    laps: 1,
+                    best_lap: time,
+                }),
+            }
+        }
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.session_unix_ms));
+        sessions.truncate(limit);
+        sessions
+    }
+
+    /// The personal best at `track` with the current setup, as a reference lap
+    pub fn personal_best_reference(&self, track: &str) -> Option<ReferenceLap> {
+        self.best_references.get(&(track.to_ascii_lowercase(), self.setup.clone())).cloned()
+    }
+}
+
+/// Wall clock time in ms since the Unix epoch
+pub fn unix_ms_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,462 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::racebox::parser::RaceBoxData;
+use crate::timing::geo::{heading_difference, segment_intersection, GeoPoint, LocalFrame, LocalPoint};
+
+/// Milliseconds in a GPS week, after which the RaceBox iTOW wraps to zero
+const GPS_WEEK_MS: f64 = 604_800_000.0;
+/// Consecutive fixes further apart than this are not interpolated between
+const MAX_SAMPLE_GAP_S: f64 = 1.0;
+/// Crossings closer together than this are ignored, so jitter on the line can't double-trigger
+const MIN_LAP_TIME_S: f64 = 10.0;
+/// Minimum time between a split and the previous crossing of any line
+const MIN_SECTOR_TIME_S: f64 = 2.0;
+/// Below this speed crossings are ignored (car pushed or parked on the line)
+const MIN_SPEED_KPH: f32 = 10.0;
+/// A crossing counts if the car's heading is within this many degrees of the line's direction
+const MAX_HEADING_ERROR_DEG: f32 = 90.0;
+
+/// A timing line across the track, from `a` to `b`.
+///
+/// When `heading_deg` is set, only crossings made while driving roughly in that
+/// direction are counted, so a car going the wrong way does not trigger a lap.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimingLine {
+    pub a: GeoPoint,
+    pub b: GeoPoint,
+    #[serde(default)]
+    pub heading_deg: Option<f32>,
+}
+
+/// A point of a lap trace: how far into the lap the car was, and when
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TracePoint {
+    /// Distance driven since the start/finish line, in metres
+    pub distance_m: f64,
+    /// Time since the start/finish line, in seconds
+    pub time_s: f64,
+    pub position: GeoPoint,
+}
+
+/// A completed lap
+#[derive(Debug, Clone)]
+pub struct Lap {
+    /// 1 for the first timed lap
+    pub number: u32,
+    pub time: Duration,
+    /// Sector times in track order; `None` where a split line was missed
+    pub sectors: Vec<Option<Duration>>,
+    /// Every fix of the lap, from the start/finish line to the start/finish line
+    pub trace: Vec<TracePoint>,
+    pub max_speed_kph: f32,
+    /// In or out lap: the car was in the pit lane during the lap
+    pub pit: bool,
+}
+
+/// A sector time, produced as soon as the line closing the sector is crossed
+#[derive(Debug, Clone, Copy)]
+pub struct SplitResult {
+    /// Number of the lap the sector belongs to
+    pub lap: u32,
+    /// 0 for the first sector
+    pub sector: usize,
+    pub time: Duration,
+    /// Best time for this sector before this one was set
+    pub previous_best: Option<Duration>,
+}
+
+impl SplitResult {
+    /// Seconds lost (positive) or gained (negative) against the previous best sector
+    pub fn delta_s(&self) -> Option<f64> {
+        self.previous_best.map(|best| self.time.as_secs_f64() - best.as_secs_f64())
+    }
+}
+
+/// What a timing line crossing meant
+#[derive(Debug, Clone)]
+pub enum LapEvent {
+    /// First crossing of the session: the out lap is over and timing begins
+    Started,
+    Split(SplitResult),
+    /// Lap finished; its closing sector is available as the timer's last split
+    Completed(Lap),
+}
+
+/// A fix projected into the timer's local frame
+#[derive(Debug, Clone, Copy)]
+struct Fix {
+    /// GNSS time in seconds, unwrapped across GPS week rollovers
+    time_s: f64,
+    position: LocalPoint,
+}
+
+/// GNSS time from the RaceBox time of week (iTOW), unwrapped so it keeps
+/// counting up past the end of the GPS week
+#[derive(Debug, Clone, Default)]
+pub struct GnssClock {
+    week_offset_ms: f64,
+    last_itow_ms: Option<u32>,
+}
+
+impl GnssClock {
+    /// GNSS time in seconds of a sample stamped `itow_ms`
+    pub fn seconds(&mut self, itow_ms: u32) -> f64 {
+        if let Some(last) = self.last_itow_ms
+            && (itow_ms as f64) < last as f64 - GPS_WEEK_MS / 2.0
+        {
+            self.week_offset_ms += GPS_WEEK_MS;
+        }
+        self.last_itow_ms = Some(itow_ms);
+        (self.week_offset_ms + itow_ms as f64) / 1000.0
+    }
+}
+
+/// A timing line projected into the timer's local frame
+#[derive(Debug, Clone, Copy)]
+struct LineSegment {
+    a: LocalPoint,
+    b: LocalPoint,
+    heading_deg: Option<f32>,
+}
+
+/// Detects start/finish and split line crossings from RaceBox fixes and keeps
+/// lap and sector times.
+///
+/// Crossing instants are interpolated between the two fixes either side of the
+/// line, using the RaceBox GNSS time of week rather than arrival time, so lap
+/// times are accurate to a few milliseconds at 25 Hz regardless of BLE jitter.
+/// Split lines are only looked for in track order, so a layout that passes near
+/// a split line elsewhere does not trigger it.
+#[derive(Debug, Clone)]
+pub struct LapTimer {
+    start_finish: TimingLine,
+    splits: Vec<TimingLine>,
+    /// Start/finish line first, then the split lines in track order
+    segments: Vec<LineSegment>,
+    frame: LocalFrame,
+    previous: Option<Fix>,
+    clock: GnssClock,
+    /// GNSS time of the last counted start/finish crossing
+    lap_start_s: Option<f64>,
+    /// GNSS time of the last counted crossing of any line
+    sector_start_s: Option<f64>,
+    /// Index of the next split line expected this lap
+    next_split: usize,
+    current_sectors: Vec<Option<Duration>>,
+    best_sectors: Vec<Option<Duration>>,
+    last_split: Option<SplitResult>,
+    laps: Vec<Lap>,
+    /// Telemetry clock time of every counted start/finish crossing, including the first
+    crossings: Vec<Duration>,
+    /// Fixes of the lap in progress
+    trace: Vec<TracePoint>,
+    /// Highest speed of the lap in progress
+    max_speed_kph: f32,
+    /// Whether the car has been in the pit lane during the lap in progress
+    pit_lap: bool,
+}
+
+impl LapTimer {
+    pub fn new(start_finish: TimingLine, splits: Vec<TimingLine>) -> Self {
+        let frame = LocalFrame::new(start_finish.a);
+        let segments = std::iter::once(&start_finish)
+            .chain(&splits)
+            .map(|line| LineSegment {
+                a: frame.to_local(line.a),
+                b: frame.to_local(line.b),
+                heading_deg: line.heading_deg,
+            })
+            .collect();
+        let sector_count = splits.len() + 1;
+        Self {
+            start_finish,
+            splits,
+            segments,
+            frame,
+            previous: None,
+            clock: GnssClock::default(),
+            lap_start_s: None,
+            sector_start_s: None,
+            next_split: 0,
+            current_sectors: vec![None; sector_count],
+            best_sectors: vec![None; sector_count],
+            last_split: None,
+            laps: Vec::new(),
+            crossings: Vec::new(),
+            trace: Vec::new(),
+            max_speed_kph: 0.0,
+            pit_lap: false,
+        }
+    }
+
+    pub fn start_finish(&self) -> &TimingLine {
+        &self.start_finish
+    }
+
+    pub fn splits(&self) -> &[TimingLine] {
+        &self.splits
+    }
+
+    /// Forget all laps and fixes, keeping the lines
+    pub fn reset(&mut self) {
+        *self = Self::new(self.start_finish, std::mem::take(&mut self.splits));
+    }
+
+    /// Start a lap at the fix in `data`, received at `now`, as if the car had
+    /// just crossed the start/finish line there. For a line placed at the car's
+    /// position, which the next fix would already be past.
+    pub fn start_lap(&mut self, data: &RaceBoxData, now: Duration) {
+        if !data.fix_ok {
+            return;
+        }
+        let geo = GeoPoint::new(data.latitude, data.longitude);
+        let fix = Fix {
+            time_s: self.clock.seconds(data.timestamp_ms),
+            position: self.frame.to_local(geo),
+        };
+        self.previous = Some(fix);
+        self.lap_start_s = Some(fix.time_s);
+        self.sector_start_s = Some(fix.time_s);
+        self.next_split = 0;
+        self.crossings.push(now);
+        self.trace = vec![TracePoint {
+            distance_m: 0.0,
+            time_s: 0.0,
+            position: geo,
+        }];
+    }
+
+    /// Feed a RaceBox sample received at `now` on the telemetry clock
+    pub fn update(&mut self, data: &RaceBoxData, now: Duration) -> Option<LapEvent> {
+        if !data.fix_ok {
+            self.previous = None;
+            return None;
+        }
+
+        let geo = GeoPoint::new(data.latitude, data.longitude);
+        let fix = Fix {
+            time_s: self.clock.seconds(data.timestamp_ms),
+            position: self.frame.to_local(geo),
+        };
+        let Some(previous) = self.previous else {
+            self.previous = Some(fix);
+            return None;
+        };
+        let dt = fix.time_s - previous.time_s;
+        if dt < 0.0 {
+            // GNSS time went back, e.g. the receiver restarted: the lap in progress
+            // can't be timed across the jump, and this fix can't end a crossing
+            self.previous = None;
+            self.abandon_lap();
+            return None;
+        }
+        if dt == 0.0 {
+            return None;
+        }
+        self.previous = Some(fix);
+        if self.lap_start_s.is_some() {
+            self.max_speed_kph = self.max_speed_kph.max(data.speed_kph);
+        }
+        if dt > MAX_SAMPLE_GAP_S || data.speed_kph < MIN_SPEED_KPH {
+            self.extend_trace(fix, geo);
+            return None;
+        }
+
+        if let Some(crossing_s) = self.crossing(0, previous, fix, data)
+            && self.lap_start_s.is_none_or(|start| crossing_s - start >= MIN_LAP_TIME_S)
+        {
+            let crossed_at = now.saturating_sub(Duration::from_secs_f64((fix.time_s - crossing_s).max(0.0)));
+            self.crossings.push(crossed_at);
+            let event = self.finish_lap(crossing_s, previous, fix);
+            self.extend_trace(fix, geo);
+            return Some(event);
+        }
+        self.extend_trace(fix, geo);
+
+        self.lap_start_s?;
+        if self.next_split < self.splits.len()
+            && let Some(crossing_s) = self.crossing(self.next_split + 1, previous, fix, data)
+            && self.sector_start_s.is_none_or(|start| crossing_s - start >= MIN_SECTOR_TIME_S)
+        {
+            let sector = self.next_split;
+            self.next_split += 1;
+            return self.close_sector(sector, crossing_s).map(LapEvent::Split);
+        }
+        None
+    }
+
+    /// Drop the lap in progress without counting it; the next start/finish
+    /// crossing starts a new one
+    fn abandon_lap(&mut self) {
+        self.lap_start_s = None;
+        self.sector_start_s = None;
+        self.next_split = 0;
+        self.current_sectors = vec![None; self.splits.len() + 1];
+        self.trace.clear();
+        self.max_speed_kph = 0.0;
+    }
+
+    /// Add `fix` to the trace of the lap in progress.
+    ///
+    /// Right after a crossing the trace starts with the interpolated point on the
+    /// line, so only the part of the move beyond the line counts towards the distance.
+    fn extend_trace(&mut self, fix: Fix, geo: GeoPoint) {
+        let (Some(start), Some(last)) = (self.lap_start_s, self.trace.last()) else {
+            return;
+        };
+        let distance_m = last.distance_m + self.frame.to_local(last.position).distance(&fix.position);
+        self.trace.push(TracePoint {
+            distance_m,
+            time_s: fix.time_s - start,
+            position: geo,
+        });
+    }
+
+    /// GNSS time at which the move from `previous` to `fix` crossed line `index`, if it did
+    fn crossing(&self, index: usize, previous: Fix, fix: Fix, data: &RaceBoxData) -> Option<f64> {
+        let segment = &self.segments[index];
+        if segment
+            .heading_deg
+            .is_some_and(|heading| heading_difference(data.heading_deg, heading) > MAX_HEADING_ERROR_DEG)
+        {
+            return None;
+        }
+        let fraction = segment_intersection(previous.position, fix.position, segment.a, segment.b)?;
+        Some(previous.time_s + (fix.time_s - previous.time_s) * fraction)
+    }
+
+    /// Record the time of `sector`, ending at `crossing_s`, and start the next one
+    fn close_sector(&mut self, sector: usize, crossing_s: f64) -> Option<SplitResult> {
+        let start = self.sector_start_s.replace(crossing_s)?;
+        let time = Duration::from_secs_f64((crossing_s - start).max(0.0));
+        let previous_best = self.best_sectors[sector];
+        if !self.pit_lap && previous_best.is_none_or(|best| time < best) {
+            self.best_sectors[sector] = Some(time);
+        }
+        self.current_sectors[sector] = Some(time);
+        let split = SplitResult {
+            lap: self.laps.len() as u32 + 1,
+            sector,
+            time,
+            previous_best,
+        };
+        self.last_split = Some(split);
+        Some(split)
+    }
+
+    fn finish_lap(&mut self, crossing_s: f64, previous: Fix, fix: Fix) -> LapEvent {
+        let fraction = (crossing_s - previous.time_s) / (fix.time_s - previous.time_s);
+        let on_line = previous.position.lerp(&fix.position, fraction);
+        let line_point = |distance_m: f64, time_s: f64| TracePoint {
+            distance_m,
+            time_s,
+            position: self.frame.to_geo(on_line),
+        };
+        let first_point = line_point(0.0, 0.0);
+
+        let Some(start) = self.lap_start_s.replace(crossing_s) else {
+            self.sector_start_s = Some(crossing_s);
+            self.trace = vec![first_point];
+            return LapEvent::Started;
+        };
+
+        let mut trace = std::mem::replace(&mut self.trace, vec![first_point]);
+        let last = trace.last().map(|p| (p.distance_m, self.frame.to_local(p.position)));
+        if let Some((distance_m, position)) = last {
+            trace.push(line_point(distance_m + position.distance(&on_line), crossing_s - start));
+        }
+
+        // The closing sector is only meaningful if every split line was crossed
+        let last_sector = self.splits.len();
+        if self.next_split == last_sector {
+            self.close_sector(last_sector, crossing_s);
+        }
+        self.sector_start_s = Some(crossing_s);
+        self.next_split = 0;
+
+        let lap = Lap {
+            number: self.laps.len() as u32 + 1,
+            time: Duration::from_secs_f64((crossing_s - start).max(0.0)),
+            sectors: std::mem::replace(&mut self.current_sectors, vec![None; last_sector + 1]),
+            trace,
+            max_speed_kph: std::mem::take(&mut self.max_speed_kph),
+            pit: std::mem::take(&mut self.pit_lap),
+        };
+        self.laps.push(lap.clone());
+        LapEvent::Completed(lap)
+    }
+
+    /// Completed laps, oldest first
+    pub fn laps(&self) -> &[Lap] {
+        &self.laps
+    }
+
+    pub fn lap_count(&self) -> usize {
+        self.laps.len()
+    }
+
+    pub fn last_lap(&self) -> Option<&Lap> {
+        self.laps.last()
+    }
+
+    /// Fastest lap, leaving out in and out laps
+    pub fn best_lap(&self) -> Option<&Lap> {
+        self.laps.iter().filter(|lap| !lap.pit).min_by_key(|lap| lap.time)
+    }
+
+    /// Mark the lap in progress as an in or out lap, so it doesn't count
+    /// towards the best lap or best sectors
+    pub fn mark_pit_lap(&mut self) {
+        self.pit_lap = true;
+    }
+
+    /// Time spent on the lap in progress, as of the latest fix
+    pub fn current_lap_time(&self) -> Option<Duration> {
+        let start = self.lap_start_s?;
+        let latest = self.previous?;
+        Some(Duration::from_secs_f64((latest.time_s - start).max(0.0)))
+    }
+
+    /// Fixes of the lap in progress, starting on the start/finish line
+    pub fn current_trace(&self) -> &[TracePoint] {
+        &self.trace
+    }
+
+    /// Sector times of the lap in progress
+    pub fn current_sectors(&self) -> &[Option<Duration>] {
+        &self.current_sectors
+    }
+
+    /// Best time for each sector this session
+    pub fn best_sectors(&self) -> &[Option<Duration>] {
+        &self.best_sectors
+    }
+
+    /// Sum of the best sectors, once every sector has been timed
+    pub fn theoretical_best(&self) -> Option<Duration> {
+        self.best_sectors.iter().copied().sum()
+    }
+
+    /// The most recent sector time, including the closing sector of the last lap
+    pub fn last_split(&self) -> Option<&SplitResult> {
+        self.last_split.as_ref()
+    }
+
+    /// Telemetry clock times of every start/finish crossing, for use as lap markers
+    pub fn crossings(&self) -> &[Duration] {
+        &self.crossings
+    }
+}
+
+/// `m:ss.mmm`, the usual lap time notation
+pub fn format_lap_time(time: Duration) -> String {
+    let ms = time.as_millis();
+    format!("{}:{:02}.{:03}", ms / 60_000, (ms / 1000) % 60, ms % 1000)
+}
+
+/// `+0.123` / `-0.123`, seconds lost or gained
+pub fn format_delta(delta_s: f64) -> String {
+    format!("{:+.3}", delta_s)
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use crate::racebox::parser::RaceBoxData;
+use crate::timing::geo::{point_in_polygon, polygon_crossing, GeoPoint, LocalFrame, LocalPoint};
+use crate::timing::lap_timer::GnssClock;
+use crate::tracks::Track;
+
+/// Below this speed the car counts as stationary in its pit box
+const STATIONARY_SPEED_KPH: f32 = 2.0;
+/// Consecutive fixes further apart than this are not interpolated between
+const MAX_SAMPLE_GAP_S: f64 = 1.0;
+
+/// A completed pit stop
+#[derive(Debug, Clone, Copy)]
+pub struct PitStop {
+    /// 1 for the first stop
+    pub number: u32,
+    /// Time from pit entry to pit exit
+    pub lane_time: Duration,
+    /// Time spent stationary in the pit lane
+    pub stationary_time: Duration,
+    pub max_speed_kph: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PitEvent {
+    Entered,
+    Exited(PitStop),
+}
+
+/// Stop in progress
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    /// GNSS time of the entry
+    time_s: f64,
+    entered_at: Duration,
+    stationary_s: f64,
+    max_speed_kph: f32,
+}
+
+/// Detects pit entry and exit from the track's pit lane polygon and times each stop.
+///
+/// Entry and exit instants are interpolated to where the car crossed the edge of
+/// the polygon, on GNSS time.
+#[derive(Debug, Clone)]
+pub struct PitMonitor {
+    frame: LocalFrame,
+    polygon: Vec<LocalPoint>,
+    speed_limit_kph: f32,
+    clock: GnssClock,
+    /// GNSS time and position of the previous fix
+    previous: Option<(f64, LocalPoint)>,
+    entry: Option<Entry>,
+    speeding: bool,
+    stops: Vec<PitStop>,
+}
+
+impl PitMonitor {
+    /// A monitor for `track`'s pit lane, or `None` if the track doesn't describe one
+    pub fn for_track(track: &Track) -> Option<Self> {
+        if track.pit_lane.len() < 3 {
+            return None;
+        }
+        let frame = LocalFrame::new(track.pit_lane[0]);
+        Some(Self {
+            frame,
+            polygon: track.pit_lane.iter().map(|p| frame.to_local(*p)).collect(),
+            speed_limit_kph: track.pit_speed_limit_kph,
+            clock: GnssClock::default(),
+            previous: None,
+            entry: None,
+            speeding: false,
+            stops: Vec::new(),
+        })
+    }
+
+    /// Forget all stops, keeping the pit lane
+    pub fn reset(&mut self) {
+        self.clock = GnssClock::default();
+        self.previous = None;
+        self.entry = None;
+        self.speeding = false;
+        self.stops.clear();
+    }
+
+    /// Feed a RaceBox sample received at `now` on the telemetry clock
+    pub fn update(&mut self, data: &RaceBoxData, now: Duration) -> Option<PitEvent> {
+        if !data.fix_ok {
+            self.previous = None;
+            return None;
+        }
+        let time_s = self.clock.seconds(data.timestamp_ms);
+        let position = self.frame.to_local(GeoPoint::new(data.latitude, data.longitude));
+        let previous = self.previous.replace((time_s, position));
+        let inside = point_in_polygon(position, &self.polygon);
+
+        // Time at which the move from the previous fix crossed into or out of the pit lane
+        let crossed_at = || match previous {
+            Some((previous_s, previous_position)) if (0.0..=MAX_SAMPLE_GAP_S).contains(&(time_s - previous_s)) => {
+                polygon_crossing(previous_position, position, &self.polygon)
+                    .map_or(time_s, |fraction| previous_s + (time_s - previous_s) * fraction)
+            }
+            _ => time_s,
+        };
+
+        match self.entry.as_mut() {
+            None if inside => {
+                let entry_s = crossed_at();
+                self.entry = Some(Entry {
+                    time_s: entry_s,
+                    entered_at: now.saturating_sub(Duration::from_secs_f64((time_s - entry_s).max(0.0))),
+                    stationary_s: 0.0,
+                    max_speed_kph: data.speed_kph,
+                });
+                self.speeding = data.speed_kph > self.speed_limit_kph;
+                Some(PitEvent::Entered)
+            }
+            None => None,
+            Some(entry) if inside => {
+                if let Some((previous_s, _)) = previous
+                    && data.speed_kph < STATIONARY_SPEED_KPH
+                {
+                    // A clock that went back adds nothing
+                    entry.stationary_s += (time_s - previous_s).clamp(0.0, MAX_SAMPLE_GAP_S);
+                }
+                entry.max_speed_kph = entry.max_speed_kph.max(data.speed_kph);
+                self.speeding = data.speed_kph > self.speed_limit_kph;
+                None
+            }
+            Some(entry) => {
+                let entry = *entry;
+                let exit_s = crossed_at();
+                let stop = PitStop {
+                    number: self.stops.len() as u32 + 1,
+                    lane_time: Duration::from_secs_f64((exit_s - entry.time_s).max(0.0)),
+                    stationary_time: Duration::from_secs_f64(entry.stationary_s.max(0.0)),
+                    max_speed_kph: entry.max_speed_kph,
+                };
+                self.entry = None;
+                self.speeding = false;
+                self.stops.push(stop);
+                Some(PitEvent::Exited(stop))
+            }
+        }
+    }
+
+    pub fn in_pit_lane(&self) -> bool {
+        self.entry.is_some()
+    }
+
+    /// Whether the car is in the pit lane above the speed limit, as of the latest fix
+    pub fn speeding(&self) -> bool {
+        self.speeding
+    }
+
+    pub fn speed_limit_kph(&self) -> f32 {
+        self.speed_limit_kph
+    }
+
+    /// Time since pit entry, on the telemetry clock, while in the pit lane
+    pub fn time_in_lane(&self, now: Duration) -> Option<Duration> {
+        self.entry.map(|entry| now.saturating_sub(entry.entered_at))
+    }
+
+    pub fn stops(&self) -> &[PitStop] {
+        &self.stops
+    }
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use crate::timing::lap_timer::Lap;
+use crate::vehicle::VehicleConfig;
+
+/// Time constant of the smoothing applied to the fuel level, to ride out slosh
+const FUEL_SMOOTHING_S: f32 = 10.0;
+/// Fuel per lap is averaged over this many recent laps
+const FUEL_LAPS_AVERAGED: usize = 3;
+/// Lap times for the time remaining are averaged over this many recent laps
+const LAP_TIMES_AVERAGED: usize = 3;
+
+/// A lap completed during the stint
+#[derive(Debug, Clone, Copy)]
+struct StintLap {
+    time: Duration,
+    pit: bool,
+    /// Mean fuel level over the lap
+    fuel_l: Option<f32>,
+}
+
+/// Tracks the current stint: its time, its laps, and how far the fuel will go.
+///
+/// The fuel sender reading swings as fuel sloshes through corners and under
+/// braking. Since every lap has the same corners, the level averaged over a whole
+/// lap cancels most of that out, so fuel per lap is taken from the difference
+/// between consecutive lap averages. The current level is smoothed over time.
+#[derive(Debug, Clone)]
+pub struct StintManager {
+    reserve_l: f32,
+    pit_window_laps: f32,
+    max_stint: Option<Duration>,
+    /// 1 for the first stint of the session
+    number: u32,
+    started_at: Duration,
+    laps: Vec<StintLap>,
+    /// Sum and count of the fuel levels seen during the lap in progress
+    lap_fuel: (f32, u32),
+    fuel_l: Option<f32>,
+    last_fuel_at: Option<Duration>,
+    /// Whether the pit window opening has been reported for this stint
+    window_announced: bool,
+}
+
+impl StintManager {
+    pub fn new(config: &VehicleConfig, now: Duration) -> Self {
+        Self {
+            reserve_l: config.fuel.reserve_l,
+            pit_window_laps: config.stint.pit_window_laps,
+            max_stint: config.stint.max_stint_min.map(|min| Duration::from_secs_f32(min * 60.0)),
+            number: 1,
+            started_at: now,
+            laps: Vec::new(),
+            lap_fuel: (0.0, 0),
+            fuel_l: None,
+            last_fuel_at: None,
+            window_announced: false,
+        }
+    }
+
+    /// Start the next stint at `now`, e.g. on pit exit
+    pub fn new_stint(&mut self, now: Duration) {
+        self.number += 1;
+        self.started_at = now;
+        self.laps.clear();
+        self.lap_fuel = (0.0, 0);
+        // Refuelling makes the smoothed level meaningless; start again from the next reading
+        self.fuel_l = None;
+        self.last_fuel_at = None;
+        self.window_announced = false;
+    }
+
+    /// Feed a calibrated fuel level received at `now`
+    pub fn on_fuel(&mut self, litres: f32, now: Duration) {
+        self.fuel_l = Some(match (self.fuel_l, self.last_fuel_at) {
+            (Some(fuel_l), Some(last)) => {
+                let dt = now.saturating_sub(last).as_secs_f32();
+                let alpha = 1.0 - (-dt / FUEL_SMOOTHING_S).exp();
+                fuel_l + (litres - fuel_l) * alpha
+            }
+            _ => litres,
+        });
+        self.last_fuel_at = Some(now);
+        self.lap_fuel.0 += litres;
+        self.lap_fuel.1 += 1;
+    }
+
+    /// Start averaging the fuel level afresh, as lap timing starts mid-lap
+    pub fn on_lap_started(&mut self) {
+        self.lap_fuel = (0.0, 0);
+    }
+
+    /// Count a completed lap. Returns true when the pit window opened with it.
+    pub fn on_lap_completed(&mut self, lap: &Lap) -> bool {
+        let (sum, count) = std::mem::take(&mut self.lap_fuel);
+        self.laps.push(StintLap {
+            time: lap.time,
+            pit: lap.pit,
+            fuel_l: (count > 0).then(|| sum / count as f32),
+        });
+        if !self.window_announced && self.pit_window_open() {
+            self.window_announced = true;
+            return true;
+        }
+        false
+    }
+
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn time(&self, now: Duration) -> Duration {
+        now.saturating_sub(self.started_at)
+    }
+
+    pub fn laps(&self) -> usize {
+        self.laps.len()
+    }
+
+    /// Smoothed fuel level, once the ESP32 has reported one
+    pub fn fuel_l(&self) -> Option<f32> {
+        self.fuel_l
+    }
+
+    /// Average fuel used per lap over the recent racing laps of this stint
+    pub fn fuel_per_lap(&self) -> Option<f32> {
+        let used: Vec<f32> = self
+            .laps
+            .windows(2)
+            .rev()
+            .filter(|pair| !pair[0].pit && !pair[1].pit)
+            .filter_map(|pair| Some(pair[0].fuel_l? - pair[1].fuel_l?))
+            .take(FUEL_LAPS_AVERAGED)
+            .collect();
+        let per_lap = used.iter().sum::<f32>() / used.len() as f32;
+        (!used.is_empty() && per_lap > 0.0).then_some(per_lap)
+    }
+
+    /// Laps the fuel above the reserve will last
+    pub fn laps_remaining(&self) -> Option<f32> {
+        Some(((self.fuel_l? - self.reserve_l) / self.fuel_per_lap()?).max(0.0))
+    }
+
+    /// Average time of the recent racing laps of this stint
+    pub fn average_lap_time(&self) -> Option<Duration> {
+        let times: Vec<Duration> = self
+            .laps
+            .iter()
+            .rev()
+            .filter(|lap| !lap.pit)
+            .map(|lap| lap.time)
+            .take(LAP_TIMES_AVERAGED)
+            .collect();
+        (!times.is_empty()).then(|| times.iter().sum::<Duration>() / times.len() as u32)
+    }
+
+    /// Driving time the fuel above the reserve will last
+    pub fn time_remaining(&self) -> Option<Duration> {
+        Some(self.average_lap_time()?.mul_f32(self.laps_remaining()?))
+    }
+
+    /// Whether it's time to pit for fuel
+    pub fn pit_window_open(&self) -> bool {
+        self.laps_remaining().is_some_and(|laps| laps <= self.pit_window_laps)
+    }
+
+    /// Whether the stint has run past the configured maximum
+    pub fn over_max_time(&self, now: Duration) -> bool {
+        self.max_stint.is_some_and(|max| self.time(now) >= max)
+    }
+}
+
+/// Format a stint time as h:mm:ss, or m:ss under an hour
+pub fn format_stint_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    match secs / 3600 {
+        0 => format!("{}:{:02}", secs / 60, secs % 60),
+        hours => format!("{}:{:02}:{:02}", hours, secs / 60 % 60, secs % 60),
+    }
+}
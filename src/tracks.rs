@@ -0,0 +1,241 @@
+pub mod learner;
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::timing::geo::GeoPoint;
+use crate::timing::lap_timer::TimingLine;
+
+const TRACKS_FILE: &str = "tracks.yml";
+/// Tracks learned from a driven lap are kept apart from the hand-edited database
+const LEARNED_TRACKS_DIR: &str = "data";
+const LEARNED_TRACKS_FILE: &str = "learned_tracks.yml";
+/// Detection radius around the start/finish line when a track doesn't set one
+const DEFAULT_GEOFENCE_RADIUS_M: f64 = 1500.0;
+/// An auto-selected track is dropped once the car is this much further out than its geofence
+const GEOFENCE_EXIT_FACTOR: f64 = 1.5;
+/// Pit lane speed limit when a track doesn't set one
+const DEFAULT_PIT_SPEED_LIMIT_KPH: f32 = 60.0;
+
+#[derive(Error, Debug)]
+pub enum TrackError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid track database: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// Direction the circuit is driven in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackDirection {
+    Clockwise,
+    Anticlockwise,
+}
+
+impl fmt::Display for TrackDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackDirection::Clockwise => write!(f, "clockwise"),
+            TrackDirection::Anticlockwise => write!(f, "anticlockwise"),
+        }
+    }
+}
+
+/// A circuit in the track database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Track {
+    pub name: String,
+    pub start_finish: TimingLine,
+    /// Split lines in track order
+    #[serde(default)]
+    pub splits: Vec<TimingLine>,
+    /// Outline of the pit lane, as a polygon
+    #[serde(default)]
+    pub pit_lane: Vec<GeoPoint>,
+    /// Speed above which a warning is shown in the pit lane
+    #[serde(default = "default_pit_speed_limit")]
+    pub pit_speed_limit_kph: f32,
+    pub length_m: f64,
+    pub direction: TrackDirection,
+    /// The track is selected when the car is within this distance of the start/finish line
+    #[serde(default = "default_geofence_radius")]
+    pub geofence_radius_m: f64,
+    /// Driven line of a lap, for drawing the track map
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outline: Vec<GeoPoint>,
+}
+
+fn default_geofence_radius() -> f64 {
+    DEFAULT_GEOFENCE_RADIUS_M
+}
+
+fn default_pit_speed_limit() -> f32 {
+    DEFAULT_PIT_SPEED_LIMIT_KPH
+}
+
+impl Track {
+    fn center(&self) -> GeoPoint {
+        GeoPoint::new(
+            (self.start_finish.a.lat + self.start_finish.b.lat) / 2.0,
+            (self.start_finish.a.lon + self.start_finish.b.lon) / 2.0,
+        )
+    }
+
+    /// Whether `position` is inside the track's geofence
+    pub fn contains(&self, position: &GeoPoint) -> bool {
+        self.center().distance_m(position) <= self.geofence_radius_m
+    }
+
+    /// Whether `position` is far enough out of the geofence to consider the car gone
+    pub fn has_left(&self, position: &GeoPoint) -> bool {
+        self.center().distance_m(position) > self.geofence_radius_m * GEOFENCE_EXIT_FACTOR
+    }
+}
+
+/// The track database, loaded from `assets/tracks.yml` plus the tracks learned
+/// from a driven lap in `data/learned_tracks.yml`.
+///
+/// Example YAML format:
+///
+/// ```yaml
+/// auto_track_mode: true
+/// tracks:
+///   - name: Example Circuit
+///     start_finish:
+///       a: { lat: 51.4870, lon: -2.2140 }
+///       b: { lat: 51.4872, lon: -2.2136 }
+///       heading_deg: 20.0
+///     splits:
+///       - a: { lat: 51.4901, lon: -2.2105 }
+///         b: { lat: 51.4899, lon: -2.2101 }
+///     pit_lane:
+///       - { lat: 51.4866, lon: -2.2148 }
+///       - { lat: 51.4869, lon: -2.2139 }
+///       - { lat: 51.4874, lon: -2.2142 }
+///     pit_speed_limit_kph: 60
+///     length_m: 2977
+///     direction: clockwise
+///     geofence_radius_m: 1500
+/// ```
+///
+/// `heading_deg`, `splits`, `pit_lane`, `pit_speed_limit_kph` (60 by default),
+/// `geofence_radius_m` and `outline`
+/// (a list of points like `pit_lane`, filled in for learned tracks) are optional.
+/// With `auto_track_mode`, entering a track's geofence also switches the
+/// dashboard to Track mode.
+///
+/// Learned tracks are written to their own file, a plain `tracks:` list, so
+/// saving one never rewrites `assets/tracks.yml`. A learned track replaces a
+/// database track of the same name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrackDatabase {
+    #[serde(default)]
+    pub auto_track_mode: bool,
+    #[serde(default)]
+    pub tracks: Vec<Track>,
+    /// The tracks that came from `data/learned_tracks.yml` or were learned since
+    #[serde(skip)]
+    learned: Vec<Track>,
+}
+
+/// Contents of `data/learned_tracks.yml`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LearnedTracks {
+    #[serde(default)]
+    tracks: Vec<Track>,
+}
+
+impl TrackDatabase {
+    /// `assets/tracks.yml` next to the executable, or in the current directory
+    pub fn default_path() -> PathBuf {
+        if let Ok(exe_path) = env::current_exe()
+            && let Some(exe_dir) = exe_path.parent()
+        {
+            let path = exe_dir.join("assets").join(TRACKS_FILE);
+            if path.exists() {
+                return path;
+            }
+        }
+        Path::new("assets").join(TRACKS_FILE)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TrackError> {
+        let yaml = fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&yaml)?)
+    }
+
+    pub fn learned_path() -> PathBuf {
+        Path::new(LEARNED_TRACKS_DIR).join(LEARNED_TRACKS_FILE)
+    }
+
+    /// Load the default database and the learned tracks. A missing or invalid
+    /// file only disables track detection, since the dashboard works fine without it.
+    pub fn load_default() -> Self {
+        let path = Self::default_path();
+        let mut db = match Self::load(&path) {
+            Ok(db) => db,
+            Err(e) => {
+                crate::telemetry_log!(log::Level::Warn, "Track database {} not loaded: {}", path.display(), e);
+                Self::default()
+            }
+        };
+        let learned_path = Self::learned_path();
+        if let Err(e) = db.merge_learned(&learned_path) {
+            crate::telemetry_log!(log::Level::Warn, "Learned tracks {} not loaded: {}", learned_path.display(), e);
+        }
+        db
+    }
+
+    /// Add the learned tracks stored at `path`; a missing file adds none
+    pub fn merge_learned<P: AsRef<Path>>(&mut self, path: P) -> Result<(), TrackError> {
+        let yaml = match fs::read_to_string(path) {
+            Ok(yaml) => yaml,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let learned: LearnedTracks = serde_yaml::from_str(&yaml)?;
+        for track in learned.tracks {
+            self.add_learned(track);
+        }
+        Ok(())
+    }
+
+    /// Add a learned `track`, replacing any track with the same name, and write
+    /// all learned tracks to `path`
+    pub fn save_track<P: AsRef<Path>>(&mut self, track: Track, path: P) -> Result<(), TrackError> {
+        self.add_learned(track);
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let learned = LearnedTracks { tracks: self.learned.clone() };
+        fs::write(path, serde_yaml::to_string(&learned)?)?;
+        Ok(())
+    }
+
+    fn add_learned(&mut self, track: Track) {
+        self.tracks.retain(|t| !t.name.eq_ignore_ascii_case(&track.name));
+        self.learned.retain(|t| !t.name.eq_ignore_ascii_case(&track.name));
+        self.tracks.push(track.clone());
+        self.learned.push(track);
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Track> {
+        self.tracks.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The nearest track whose geofence contains `position`
+    pub fn detect(&self, position: &GeoPoint) -> Option<&Track> {
+        self.tracks
+            .iter()
+            .filter(|t| t.contains(position))
+            .min_by(|a, b| a.center().distance_m(position).total_cmp(&b.center().distance_m(position)))
+    }
+}
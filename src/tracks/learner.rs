@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use crate::racebox::parser::RaceBoxData;
+use crate::timing::geo::{GeoPoint, LocalFrame, LocalPoint};
+use crate::timing::lap_timer::{Lap, LapEvent, LapTimer, TimingLine, TracePoint};
+use crate::tracks::{Track, TrackDirection, DEFAULT_GEOFENCE_RADIUS_M, DEFAULT_PIT_SPEED_LIMIT_KPH};
+
+/// Half the width of generated timing lines, in metres
+const LINE_HALF_WIDTH_M: f64 = 15.0;
+/// Number of sectors the learned lap is divided into
+const SECTOR_COUNT: usize = 3;
+/// Minimum spacing of the points kept for the track outline, in metres
+const OUTLINE_SPACING_M: f64 = 10.0;
+
+/// Builds a new track from one driven lap.
+///
+/// The driver marks the start/finish line as they cross it; a timing line is
+/// placed there, across the direction of travel, and the next crossing closes
+/// the lap. The lap's trace gives the outline, length and direction, and split
+/// lines are placed at even distances along it.
+#[derive(Debug, Clone)]
+pub struct TrackLearner {
+    name: String,
+    /// Set once the start/finish line has been marked
+    timer: Option<LapTimer>,
+}
+
+impl TrackLearner {
+    pub fn new(name: String) -> Self {
+        Self { name, timer: None }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the start/finish line has been marked and the lap is being driven
+    pub fn is_recording(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    /// Distance driven since the start/finish line was marked
+    pub fn distance_m(&self) -> Option<f64> {
+        self.timer.as_ref()?.current_trace().last().map(|p| p.distance_m)
+    }
+
+    /// Place the start/finish line at the car's position, across its heading,
+    /// and start the lap there
+    pub fn mark_start_finish(&mut self, data: &RaceBoxData, now: Duration) {
+        let position = GeoPoint::new(data.latitude, data.longitude);
+        let line = line_across(LocalFrame::new(position), LocalPoint { x: 0.0, y: 0.0 }, data.heading_deg as f64);
+        let mut timer = LapTimer::new(line, Vec::new());
+        timer.start_lap(data, now);
+        self.timer = Some(timer);
+    }
+
+    /// Feed a RaceBox sample; returns the learned track once the lap is closed
+    pub fn update(&mut self, data: &RaceBoxData, now: Duration) -> Option<Track> {
+        let timer = self.timer.as_mut()?;
+        match timer.update(data, now)? {
+            LapEvent::Completed(lap) => Some(build_track(self.name.clone(), *timer.start_finish(), &lap)),
+            _ => None,
+        }
+    }
+}
+
+/// Turn a lap driven from a marked start/finish line into a track
+fn build_track(name: String, start_finish: TimingLine, lap: &Lap) -> Track {
+    let frame = LocalFrame::new(start_finish.a);
+    let length_m = lap.trace.last().map(|p| p.distance_m).unwrap_or_default();
+    let splits = (1..SECTOR_COUNT)
+        .filter_map(|k| split_line(&frame, &lap.trace, length_m * k as f64 / SECTOR_COUNT as f64))
+        .collect();
+
+    let mut outline: Vec<GeoPoint> = Vec::new();
+    for point in &lap.trace {
+        if outline.last().is_none_or(|last| last.distance_m(&point.position) >= OUTLINE_SPACING_M) {
+            outline.push(point.position);
+        }
+    }
+
+    // Shoelace formula: positive area means the outline runs anticlockwise
+    let local: Vec<LocalPoint> = outline.iter().map(|p| frame.to_local(*p)).collect();
+    let area: f64 = local
+        .iter()
+        .zip(local.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+    let direction = if area > 0.0 { TrackDirection::Anticlockwise } else { TrackDirection::Clockwise };
+
+    Track {
+        name,
+        start_finish,
+        splits,
+        pit_lane: Vec::new(),
+        pit_speed_limit_kph: DEFAULT_PIT_SPEED_LIMIT_KPH,
+        length_m,
+        direction,
+        geofence_radius_m: DEFAULT_GEOFENCE_RADIUS_M.max(length_m / 2.0),
+        outline,
+    }
+}
+
+/// A timing line through `center`, perpendicular to `heading_deg` (compass degrees)
+fn line_across(frame: LocalFrame, center: LocalPoint, heading_deg: f64) -> TimingLine {
+    let heading = heading_deg.to_radians();
+    // Unit vector to the left of the direction of travel (x east, y north)
+    let (left_x, left_y) = (-heading.cos(), heading.sin());
+    let offset = |sign: f64| LocalPoint {
+        x: center.x + sign * left_x * LINE_HALF_WIDTH_M,
+        y: center.y + sign * left_y * LINE_HALF_WIDTH_M,
+    };
+    TimingLine {
+        a: frame.to_geo(offset(1.0)),
+        b: frame.to_geo(offset(-1.0)),
+        heading_deg: Some(heading_deg.rem_euclid(360.0) as f32),
+    }
+}
+
+/// A split line across the trace at `distance_m` into the lap
+fn split_line(frame: &LocalFrame, trace: &[TracePoint], distance_m: f64) -> Option<TimingLine> {
+    let index = trace.partition_point(|p| p.distance_m < distance_m);
+    let before = frame.to_local(trace.get(index.checked_sub(1)?)?.position);
+    let after = frame.to_local(trace.get(index + 1).or(trace.get(index))?.position);
+    let center = frame.to_local(trace.get(index)?.position);
+    let heading_deg = (after.x - before.x).atan2(after.y - before.y).to_degrees();
+    Some(line_across(*frame, center, heading_deg))
+}
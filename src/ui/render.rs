@@ -4,15 +4,29 @@ use femtovg::{Canvas, renderer::Renderer, Color, Paint, Path};
 use crate::telemetry::SharedTelemetryState;
 use crate::logging::UI_NAMESPACE;
 use log::debug;
-use crate::ui::widgets::{Widget, WidgetGeometry};
+use crate::ui::widgets::{ThemeTransition, Widget, WidgetGeometry};
 use crate::ui::widgets::g_force_meter::GForceMeter;
 use crate::ui::theme::Theme;
-use crate::telemetry::{DriveMode, ColorScheme};
+use crate::telemetry::{DriveMode, ColorScheme, DashboardPage};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
 use crate::ui::widgets::turbo_pressure_gauge::TurboPressureGauge;
 use crate::ui::widgets::rpm_gauge::RpmGauge;
+use crate::ui::widgets::log_overlay::LogOverlay;
+use crate::ui::widgets::diagnostics_panel::DiagnosticsPanel;
+use crate::ui::widgets::alert_banner::AlertBanner;
+use crate::ui::widgets::telltales::Telltales;
+use crate::ui::widgets::shift_light::{ShiftLight, ShiftLightConfig};
+use crate::ui::widgets::gear_indicator::GearIndicator;
+use crate::ui::widgets::speedometer::Speedometer;
+use crate::ui::widgets::fuel_gauge::FuelGauge;
+use crate::ui::widgets::temperature_gauge::TemperatureGauge;
+use crate::ui::widgets::tyre_panel::TyrePanel;
+use crate::ui::widgets::input_trace::InputTrace;
+use crate::ui::widgets::lap_timer::LapTimerDisplay;
+use crate::ui::widgets::track_map::TrackMap;
+use crate::timing::lap_timer::format_lap_time;
 
 #[derive(Clone, Copy)]
 enum EasingFunction {
@@ -93,21 +107,31 @@ impl ThemeTransitionState {
 thread_local! {
     static THEME_TRANSITION_STATE: RefCell<Option<ThemeTransitionState>> = RefCell::new(None);
     static LAST_PRESET: RefCell<Option<(DriveMode, ColorScheme)>> = RefCell::new(None);
+    /// Time of the previous frame, for widget animations
+    static LAST_FRAME: RefCell<Option<Instant>> = const { RefCell::new(None) };
+    /// Widgets that keep state from frame to frame
+    static TELLTALES: RefCell<Option<Telltales>> = const { RefCell::new(None) };
+    /// Shift points, shared by the shift light and the gear indicator
+    static SHIFT_LIGHT_CONFIG: ShiftLightConfig = ShiftLightConfig::load_default();
+    static SHIFT_LIGHT: RefCell<Option<ShiftLight>> = const { RefCell::new(None) };
+    static GEAR_INDICATOR: RefCell<Option<GearIndicator>> = const { RefCell::new(None) };
 }
 
 pub fn render_ui<R: Renderer>(canvas: &mut Canvas<R>, telemetry_state: &SharedTelemetryState) {
     //debug!(target: UI_NAMESPACE, "Rendering UI {}x{}", canvas.width(), canvas.height());
 
-    // Get drive mode and color scheme from state
-    let (drive_mode, color_scheme) = {
-        let state = match telemetry_state.try_lock() {
+    // Get drive mode, color scheme and page from state
+    let (drive_mode, color_scheme, page) = {
+        let mut state = match telemetry_state.try_lock() {
             Ok(state) => state,
             Err(_) => {
                 debug!(target: UI_NAMESPACE, "Could not acquire telemetry state lock, skipping telemetry data");
                 return;
             }
         };
-        (state.get_drive_mode(), state.get_color_scheme())
+        // This frame shows the latest samples, so it closes their latency measurement
+        state.health.record_render(Instant::now());
+        (state.get_drive_mode(), state.get_color_scheme(), state.page)
     };
     let target_theme = Theme::from_preset(drive_mode, color_scheme);
 
@@ -135,9 +159,31 @@ pub fn render_ui<R: Renderer>(canvas: &mut Canvas<R>, telemetry_state: &SharedTe
         LAST_PRESET.with(|lp| *lp.borrow_mut() = Some((drive_mode, color_scheme)));
     }
 
+    let now = Instant::now();
+    let dt = LAST_FRAME.with(|last| last.replace(Some(now)).map_or(Duration::ZERO, |last| now - last));
+
     // Clear the canvas with the theme's background color
     canvas.clear_rect(0, 0, canvas.width() as u32, canvas.height() as u32, Theme::color4(theme.background_color));
 
+    match page {
+        DashboardPage::Main => {}
+        DashboardPage::Tyres => {
+            render_tyres_page(canvas, telemetry_state, &theme);
+            canvas.flush();
+            return;
+        }
+        DashboardPage::Map => {
+            render_map_page(canvas, telemetry_state, &theme);
+            canvas.flush();
+            return;
+        }
+        DashboardPage::Diagnostics => {
+            render_diagnostics_page(canvas, telemetry_state, &theme);
+            canvas.flush();
+            return;
+        }
+    }
+
     // Create a GForceMeter widget
     let mut g_force_meter = GForceMeter::new(
         theme.clone(),
@@ -191,20 +237,175 @@ pub fn render_ui<R: Renderer>(canvas: &mut Canvas<R>, telemetry_state: &SharedTe
     );
     rpm_gauge.render(canvas, rpm_gauge_rect, telemetry_state);
 
-    // Draw some text
-    let mut text_paint = Paint::color(Theme::color3(theme.text_color));
-    text_paint.set_font_size(48.0);
-    let _ = canvas.fill_text(50.0, 100.0, "VX220 Dashboard", &text_paint);
+    // Gear indicator, in the lower half of the RPM gauge
+    let gear_indicator_rect = WidgetGeometry::new(
+        canvas.width() * 0.45,
+        canvas.height() * 0.3 + canvas.width() * 0.16,
+        canvas.width() * 0.1,
+        canvas.width() * 0.09,
+    );
+    GEAR_INDICATOR.with(|gear_indicator| {
+        let mut gear_indicator = gear_indicator.borrow_mut();
+        let gear_indicator = gear_indicator
+            .get_or_insert_with(|| GearIndicator::new(theme.clone(), SHIFT_LIGHT_CONFIG.with(|config| config.clone())));
+        gear_indicator.on_theme_change(&theme, ThemeTransition { from: theme.clone(), to: theme.clone(), progress: 1.0 });
+        gear_indicator.render(canvas, gear_indicator_rect, telemetry_state);
+    });
+
+    // Speedometer, top centre, with unit, source and arc from the settings
+    let speedometer = telemetry_state.try_lock().ok().map(|state| {
+        let mut speedometer = Speedometer::new(&theme, &state.settings);
+        speedometer.set_value(state.speed());
+        speedometer
+    });
+    let speedometer_rect = WidgetGeometry::new(
+        canvas.width() * 0.4,
+        canvas.height() * 0.09,
+        canvas.width() * 0.2,
+        canvas.height() * 0.2,
+    );
+    if let Some(speedometer) = speedometer {
+        speedometer.render(canvas, speedometer_rect, telemetry_state);
+    }
+
+    // Fuel level, top right below the telltales
+    let fuel_gauge = telemetry_state.try_lock().ok().map(|state| {
+        let mut fuel_gauge = FuelGauge::new(&theme, &state.vehicle.fuel);
+        if let Some(litres) = state.fuel_litres() {
+            fuel_gauge.set_value(litres);
+        }
+        fuel_gauge
+    });
+    let fuel_gauge_rect = WidgetGeometry::new(
+        canvas.width() * 0.65,
+        canvas.height() * 0.11,
+        canvas.width() * 0.3,
+        canvas.height() * 0.07,
+    );
+    if let Some(fuel_gauge) = fuel_gauge {
+        fuel_gauge.render(canvas, fuel_gauge_rect, telemetry_state);
+    }
+
+    // Coolant and oil temperature, side by side at the right edge
+    let mut coolant_gauge = TemperatureGauge::coolant(&theme);
+    let mut oil_gauge = TemperatureGauge::oil(&theme);
+    if let Ok(state) = telemetry_state.try_lock() {
+        if let Some(temp) = state.latest_esp32_data.coolant_temp_c() {
+            coolant_gauge.set_value(temp);
+        }
+        if let Some(temp) = state.latest_esp32_data.oil_temp_c() {
+            oil_gauge.set_value(temp);
+        }
+    }
+    let coolant_gauge_rect = WidgetGeometry::new(
+        canvas.width() * 0.9,
+        canvas.height() * 0.3,
+        canvas.width() * 0.05,
+        canvas.height() * 0.5,
+    );
+    let oil_gauge_rect = WidgetGeometry::new(
+        canvas.width() * 0.95,
+        canvas.height() * 0.3,
+        canvas.width() * 0.05,
+        canvas.height() * 0.5,
+    );
+    coolant_gauge.render(canvas, coolant_gauge_rect, telemetry_state);
+    oil_gauge.render(canvas, oil_gauge_rect, telemetry_state);
+
+    // Driver inputs, above the log overlay
+    let input_trace = InputTrace::new(theme.clone());
+    let input_trace_rect = WidgetGeometry::new(
+        canvas.width() * 0.05,
+        canvas.height() * 0.71,
+        canvas.width() * 0.84,
+        canvas.height() * 0.1,
+    );
+    input_trace.render(canvas, input_trace_rect, telemetry_state);
+
+    // Latest RaceBox/ESP32 warnings and errors, along the bottom of the screen
+    let log_overlay = LogOverlay::new(theme.clone());
+    let log_overlay_rect = WidgetGeometry::new(
+        canvas.width() * 0.05,
+        canvas.height() * 0.82,
+        canvas.width() * 0.9,
+        canvas.height() * 0.15,
+    );
+    log_overlay.render(canvas, log_overlay_rect, telemetry_state);
+
+    // Shift light, top left
+    let shift_light_rect = WidgetGeometry::new(
+        canvas.width() * 0.05,
+        canvas.height() * 0.03,
+        canvas.width() * 0.42,
+        canvas.height() * 0.05,
+    );
+    let (rpm, gear) = match telemetry_state.try_lock() {
+        Ok(state) => (state.latest_esp32_data.rpm.map(|rpm| rpm as f32), state.latest_esp32_data.gear_position),
+        Err(_) => (None, None),
+    };
+    SHIFT_LIGHT.with(|shift_light| {
+        let mut shift_light = shift_light.borrow_mut();
+        let shift_light = shift_light.get_or_insert_with(|| ShiftLight::new(SHIFT_LIGHT_CONFIG.with(|config| config.clone())));
+        shift_light.update(dt);
+        shift_light.set_drive_mode(drive_mode);
+        shift_light.set_value(rpm, gear);
+        shift_light.render(canvas, shift_light_rect, telemetry_state);
+    });
+
+    // Warning and indicator lamps, top right
+    let telltales_rect = WidgetGeometry::new(
+        canvas.width() * 0.5,
+        canvas.height() * 0.02,
+        canvas.width() * 0.45,
+        canvas.height() * 0.08,
+    );
+    TELLTALES.with(|telltales| {
+        let mut telltales = telltales.borrow_mut();
+        let telltales = telltales.get_or_insert_with(|| Telltales::new(theme.clone()));
+        telltales.on_theme_change(&theme, ThemeTransition { from: theme.clone(), to: theme.clone(), progress: 1.0 });
+        telltales.update(dt);
+        telltales.render(canvas, telltales_rect, telemetry_state);
+    });
 
-    // Draw debug info
-    let mut debug_paint = Paint::color(Theme::color3(theme.text_color));
-    debug_paint.set_font_size(24.0);
-    let _ = canvas.fill_text(
-        50.0,
-        150.0,
-        &format!("Canvas size: {}x{} | Mode: {:?} | Scheme: {:?}", canvas.width(), canvas.height(), drive_mode, color_scheme),
-        &debug_paint,
+    // Lap times and delta, top left in Track mode, in place of the title
+    if drive_mode == DriveMode::Track {
+        let lap_timer = LapTimerDisplay::new(theme.clone());
+        let lap_timer_rect = WidgetGeometry::new(
+            canvas.width() * 0.05,
+            canvas.height() * 0.09,
+            canvas.width() * 0.33,
+            canvas.height() * 0.2,
+        );
+        lap_timer.render(canvas, lap_timer_rect, telemetry_state);
+    }
+
+    // Most urgent driver alert (pit lane, ...), below the gauges over the input
+    // trace, so the lap timer and speedometer stay visible
+    let alert_banner = AlertBanner::new(theme.clone());
+    let alert_banner_rect = WidgetGeometry::new(
+        canvas.width() * 0.05,
+        canvas.height() * 0.71,
+        canvas.width() * 0.84,
+        canvas.height() * 0.1,
     );
+    alert_banner.render(canvas, alert_banner_rect, telemetry_state);
+
+    if drive_mode == DriveMode::Road {
+        // Draw some text
+        let mut text_paint = Paint::color(Theme::color3(theme.text_color));
+        text_paint.set_font_size(48.0);
+        let _ = canvas.fill_text(50.0, 100.0, "VX220 Dashboard", &text_paint);
+
+        // Draw debug info
+        let mut debug_paint = Paint::color(Theme::color3(theme.text_color));
+        debug_paint.set_font_size(24.0);
+        let _ = canvas.fill_text(
+            50.0,
+            150.0,
+            &format!("Canvas size: {}x{} | Mode: {:?} | Scheme: {:?}", canvas.width(), canvas.height(), drive_mode, color_scheme),
+            &debug_paint,
+        );
+    }
 
     // Draw telemetry data if available
     let state = match telemetry_state.try_lock() {
@@ -237,6 +438,112 @@ pub fn render_ui<R: Renderer>(canvas: &mut Canvas<R>, telemetry_state: &SharedTe
         );
     }
 
+    // Summary of the last track session from the lap history, on the road screen
+    if drive_mode == DriveMode::Road
+        && let Some(session) = state.lap_history.recent_sessions(1).first()
+    {
+        y_position += y_spacing;
+        let _ = canvas.fill_text(
+            x_position,
+            y_position,
+            format!(
+                "Last session: {} | {} laps | best {}",
+                session.track,
+                session.laps,
+                session.best_lap.map(format_lap_time).unwrap_or_else(|| "-".to_string())
+            ),
+            &text_paint,
+        );
+    }
+
     // Force a flush of the canvas
     canvas.flush();
-} 
+}
+
+/// Tyre pressures and temperatures around the car, with the alert banner above
+fn render_tyres_page<R: Renderer>(canvas: &mut Canvas<R>, telemetry_state: &SharedTelemetryState, theme: &Theme) {
+    let mut title_paint = Paint::color(Theme::color3(theme.text_color));
+    title_paint.set_font_size(48.0);
+    let _ = canvas.fill_text(50.0, 100.0, "Tyres", &title_paint);
+
+    let tyre_panel = TyrePanel::new(theme.clone());
+    let tyre_panel_rect = WidgetGeometry::new(
+        canvas.width() * 0.2,
+        canvas.height() * 0.2,
+        canvas.width() * 0.6,
+        canvas.height() * 0.75,
+    );
+    tyre_panel.render(canvas, tyre_panel_rect, telemetry_state);
+
+    let alert_banner = AlertBanner::new(theme.clone());
+    let alert_banner_rect = WidgetGeometry::new(
+        canvas.width() * 0.3,
+        canvas.height() * 0.04,
+        canvas.width() * 0.65,
+        canvas.height() * 0.1,
+    );
+    alert_banner.render(canvas, alert_banner_rect, telemetry_state);
+}
+
+/// Track map with the car's position, lap times beside it and the alert banner above
+fn render_map_page<R: Renderer>(canvas: &mut Canvas<R>, telemetry_state: &SharedTelemetryState, theme: &Theme) {
+    let title = match telemetry_state.try_lock() {
+        Ok(state) => state.active_track.as_ref().map_or_else(|| "Map".to_string(), |track| track.name.clone()),
+        Err(_) => "Map".to_string(),
+    };
+    let mut title_paint = Paint::color(Theme::color3(theme.text_color));
+    title_paint.set_font_size(48.0);
+    let _ = canvas.fill_text(50.0, 100.0, &title, &title_paint);
+
+    let track_map = TrackMap::new(theme.clone());
+    let track_map_rect = WidgetGeometry::new(
+        canvas.width() * 0.05,
+        canvas.height() * 0.2,
+        canvas.width() * 0.55,
+        canvas.height() * 0.75,
+    );
+    track_map.render(canvas, track_map_rect, telemetry_state);
+
+    let lap_timer = LapTimerDisplay::new(theme.clone());
+    let lap_timer_rect = WidgetGeometry::new(
+        canvas.width() * 0.64,
+        canvas.height() * 0.3,
+        canvas.width() * 0.32,
+        canvas.height() * 0.22,
+    );
+    lap_timer.render(canvas, lap_timer_rect, telemetry_state);
+
+    let alert_banner = AlertBanner::new(theme.clone());
+    let alert_banner_rect = WidgetGeometry::new(
+        canvas.width() * 0.3,
+        canvas.height() * 0.04,
+        canvas.width() * 0.65,
+        canvas.height() * 0.1,
+    );
+    alert_banner.render(canvas, alert_banner_rect, telemetry_state);
+}
+
+/// Source health table, with the warning overlay below it
+fn render_diagnostics_page<R: Renderer>(canvas: &mut Canvas<R>, telemetry_state: &SharedTelemetryState, theme: &Theme) {
+    let mut title_paint = Paint::color(Theme::color3(theme.text_color));
+    title_paint.set_font_size(48.0);
+    let _ = canvas.fill_text(50.0, 100.0, "Diagnostics", &title_paint);
+
+    let diagnostics = DiagnosticsPanel::new(theme.clone());
+    let diagnostics_rect = WidgetGeometry::new(
+        canvas.width() * 0.05,
+        canvas.height() * 0.2,
+        canvas.width() * 0.9,
+        canvas.height() * 0.5,
+    );
+    diagnostics.render(canvas, diagnostics_rect, telemetry_state);
+
+    let log_overlay = LogOverlay::new(theme.clone());
+    let log_overlay_rect = WidgetGeometry::new(
+        canvas.width() * 0.05,
+        canvas.height() * 0.82,
+        canvas.width() * 0.9,
+        canvas.height() * 0.15,
+    );
+    log_overlay.render(canvas, log_overlay_rect, telemetry_state);
+}
@@ -0,0 +1,53 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::telemetry::alerts::AlertLevel;
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::time::Duration;
+
+/// A banner showing the most urgent driver alert (e.g. the pit lane speed limit)
+pub struct AlertBanner {
+    theme: Theme,
+}
+
+impl AlertBanner {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl Widget for AlertBanner {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let Some(alert) = telemetry_state.try_lock().ok().and_then(|state| state.alerts().into_iter().next()) else {
+            return;
+        };
+
+        let (background, text) = match alert.level {
+            AlertLevel::Critical => (Color::rgb(230, 40, 40), Color::rgb(255, 255, 255)),
+            AlertLevel::Warning => (Color::rgb(255, 170, 0), Color::rgb(0, 0, 0)),
+            AlertLevel::Info => {
+                let bg = self.theme.background_color;
+                (Color::rgba(bg[0], bg[1], bg[2], 220), Theme::color3(self.theme.text_color))
+            }
+        };
+        let mut path = Path::new();
+        path.rounded_rect(rect.x, rect.y, rect.width, rect.height, rect.height * 0.2);
+        canvas.fill_path(&path, &Paint::color(background));
+
+        let mut paint = Paint::color(text);
+        paint.set_font_size(rect.height * 0.6);
+        paint.set_text_align(Align::Center);
+        paint.set_text_baseline(Baseline::Middle);
+        let _ = canvas.fill_text(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0, &alert.message, &paint);
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 800.0, 80.0)
+    }
+}
@@ -0,0 +1,303 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::ui::widgets::gauge::GaugeGraduations;
+use crate::ui::widgets::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::time::Duration;
+
+/// Font sizes in `BarGaugeProps` are for a bar gauge this thick (the height of a
+/// horizontal one, the width of a vertical one), and scaled for other sizes
+const BAR_GAUGE_DESIGN_REFERENCE_THICKNESS: f32 = 60.0;
+/// Opacity of an unlit segment
+const OFF_ALPHA: u8 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarOrientation {
+    /// Filling from left to right
+    Horizontal,
+    /// Filling from bottom to top
+    Vertical,
+}
+
+/// Colours of a bar gauge
+pub struct BarGaugeStyle {
+    pub bar_color: [u8; 4],          // Fill colour while the value is in the normal range
+    pub warning_color: [u8; 4],      // Fill colour in either warning zone
+    pub danger_color: [u8; 4],       // Fill colour in the danger zone
+    pub background_color: [u8; 4],   // Colour of the empty part of the bar
+    pub border_color: [u8; 4],
+    pub border_width: f32,
+    pub tick_width: f32,
+}
+
+/// Properties defining the appearance of a bar gauge, on the same scale, zone and
+/// graduation model as `GaugeProps`.
+///
+/// Zones are marked along the bar and colour the fill while the value is in them:
+/// the danger zone runs from `danger_zone_start` to the maximum, the high warning
+/// zone from `warning_zone_start` up to the danger zone, and the low warning zone
+/// from the minimum up to `low_warning_zone_end`.
+pub struct BarGaugeProps {
+    pub label: String,
+    pub unit: String,
+    pub min_value: f32,
+    pub max_value: f32,
+    pub danger_zone_start: Option<f32>,
+    pub warning_zone_start: Option<f32>,
+    pub low_warning_zone_end: Option<f32>,
+    pub graduations: GaugeGraduations,
+    pub orientation: BarOrientation,
+    /// Draw the bar as this many separate segments instead of a continuous fill
+    pub segments: Option<usize>,
+    pub style: BarGaugeStyle,
+    pub label_font_size: f32,
+    pub value_font_size: f32,
+    pub value_decimals: u8,
+}
+
+/// Which zone of the scale a value is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Zone {
+    Normal,
+    Warning,
+    Danger,
+}
+
+/// A straight gauge: a bar along a scale with graduations, warning and danger
+/// zones, a label and the current value.
+///
+/// The value is shown as `--` until one is set.
+pub struct BarGauge {
+    pub props: BarGaugeProps,
+    pub value: Option<f32>,
+    theme: Theme,
+}
+
+impl BarGauge {
+    pub fn new(props: BarGaugeProps, theme: &Theme) -> Self {
+        Self {
+            props,
+            value: None,
+            theme: theme.clone(),
+        }
+    }
+
+    pub fn set_value(&mut self, value: f32) {
+        self.value = Some(value);
+    }
+
+    fn zone(&self, value: f32) -> Zone {
+        let props = &self.props;
+        if props.danger_zone_start.is_some_and(|start| value >= start) {
+            Zone::Danger
+        } else if props.warning_zone_start.is_some_and(|start| value >= start)
+            || props.low_warning_zone_end.is_some_and(|end| value < end)
+        {
+            Zone::Warning
+        } else {
+            Zone::Normal
+        }
+    }
+
+    fn zone_color(&self, zone: Zone) -> [u8; 4] {
+        match zone {
+            Zone::Normal => self.props.style.bar_color,
+            Zone::Warning => self.props.style.warning_color,
+            Zone::Danger => self.props.style.danger_color,
+        }
+    }
+
+    /// Position of `value` along the scale, from 0 at the minimum to 1 at the maximum
+    fn fraction(&self, value: f32) -> f32 {
+        let props = &self.props;
+        ((value - props.min_value) / (props.max_value - props.min_value)).clamp(0.0, 1.0)
+    }
+
+    /// The part of `bar` between scale fractions `from` and `to`
+    fn span(&self, bar: WidgetGeometry, from: f32, to: f32) -> WidgetGeometry {
+        match self.props.orientation {
+            BarOrientation::Horizontal => WidgetGeometry::new(bar.x + bar.width * from, bar.y, bar.width * (to - from), bar.height),
+            BarOrientation::Vertical => {
+                WidgetGeometry::new(bar.x, bar.y + bar.height * (1.0 - to), bar.width, bar.height * (to - from))
+            }
+        }
+    }
+
+    /// Zones as (start fraction, end fraction, zone), for the markings along the bar
+    fn zones(&self) -> Vec<(f32, f32, Zone)> {
+        let props = &self.props;
+        let mut zones = Vec::new();
+        if let Some(end) = props.low_warning_zone_end {
+            zones.push((0.0, self.fraction(end), Zone::Warning));
+        }
+        let danger = props.danger_zone_start.map(|start| self.fraction(start));
+        if let Some(start) = props.warning_zone_start {
+            zones.push((self.fraction(start), danger.unwrap_or(1.0), Zone::Warning));
+        }
+        if let Some(start) = danger {
+            zones.push((start, 1.0, Zone::Danger));
+        }
+        zones
+    }
+}
+
+fn rgba(color: [u8; 4]) -> Color {
+    Color::rgba(color[0], color[1], color[2], color[3])
+}
+
+fn fill_rect<R: Renderer>(canvas: &mut Canvas<R>, rect: WidgetGeometry, color: Color) {
+    let mut path = Path::new();
+    path.rect(rect.x, rect.y, rect.width, rect.height);
+    canvas.fill_path(&path, &Paint::color(color));
+}
+
+impl Widget for BarGauge {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, _telemetry_state: &SharedTelemetryState) {
+        let props = &self.props;
+        let style = &props.style;
+        let horizontal = props.orientation == BarOrientation::Horizontal;
+        let font_scale_factor = if horizontal { rect.height } else { rect.width } / BAR_GAUGE_DESIGN_REFERENCE_THICKNESS;
+        let label_font_size = props.label_font_size * font_scale_factor;
+        let value_font_size = props.value_font_size * font_scale_factor;
+        let text_color = Theme::color3(self.theme.text_color);
+
+        // LAYOUT:
+        // Horizontal: label and value on a line above the bar, graduations below it.
+        // Vertical: label above the bar, value below it, graduations to its right.
+        let bar = if horizontal {
+            let top = rect.y + value_font_size * 1.1;
+            let height = (rect.y + rect.height - top) * if props.graduations.show_labels { 0.4 } else { 0.7 };
+            WidgetGeometry::new(rect.x, top, rect.width, height)
+        } else {
+            let top = rect.y + label_font_size * 1.4;
+            let bottom = rect.y + rect.height - value_font_size * 1.4;
+            WidgetGeometry::new(rect.x, top, rect.width * 0.3, bottom - top)
+        };
+        let thickness = if horizontal { bar.height } else { bar.width };
+
+        // --- DRAW BAR ---
+        let value_fraction = self.value.map_or(0.0, |value| self.fraction(value));
+        match props.segments {
+            Some(segments) if segments > 0 => {
+                // Each segment takes the colour of the zone its middle is in, dimmed while unlit
+                let lit = (value_fraction * segments as f32).ceil() as usize;
+                let gap = 0.15 / segments as f32;
+                for i in 0..segments {
+                    let from = i as f32 / segments as f32;
+                    let to = (i + 1) as f32 / segments as f32 - gap;
+                    let middle = props.min_value + (from + to) / 2.0 * (props.max_value - props.min_value);
+                    let [r, g, b, a] = self.zone_color(self.zone(middle));
+                    let color = if i < lit { Color::rgba(r, g, b, a) } else { Color::rgba(r, g, b, OFF_ALPHA) };
+                    fill_rect(canvas, self.span(bar, from, to), color);
+                }
+            }
+            _ => {
+                fill_rect(canvas, bar, rgba(style.background_color));
+                if let Some(value) = self.value {
+                    let color = rgba(self.zone_color(self.zone(value)));
+                    fill_rect(canvas, self.span(bar, 0.0, value_fraction), color);
+                }
+                if style.border_width > 0.0 {
+                    let mut border = Path::new();
+                    border.rect(bar.x, bar.y, bar.width, bar.height);
+                    let mut border_paint = Paint::color(rgba(style.border_color));
+                    border_paint.set_line_width(style.border_width);
+                    canvas.stroke_path(&border, &border_paint);
+                }
+            }
+        }
+
+        // --- DRAW ZONE MARKINGS ---
+        // A strip along the scale side of the bar, like the danger arc of the round gauge
+        let strip = thickness * 0.12;
+        for (from, to, zone) in self.zones() {
+            let span = self.span(bar, from, to);
+            let marking = if horizontal {
+                WidgetGeometry::new(span.x, bar.y + bar.height + strip * 0.5, span.width, strip)
+            } else {
+                WidgetGeometry::new(bar.x + bar.width + strip * 0.5, span.y, strip, span.height)
+            };
+            fill_rect(canvas, marking, rgba(self.zone_color(zone)));
+        }
+
+        // --- DRAW TICKS AND LABELS ---
+        let graduations = &props.graduations;
+        let tick_origin = 2.0 * strip;
+        let mut tick_paint = Paint::color(text_color);
+        tick_paint.set_line_width(style.tick_width);
+        let mut tick_label_paint = Paint::color(text_color);
+        tick_label_paint.set_font_size(label_font_size * 0.7);
+        if horizontal {
+            tick_label_paint.set_text_align(Align::Center);
+            tick_label_paint.set_text_baseline(Baseline::Top);
+        } else {
+            tick_label_paint.set_text_align(Align::Left);
+            tick_label_paint.set_text_baseline(Baseline::Middle);
+        }
+        let mut tick_value = props.min_value;
+        while tick_value <= props.max_value + 0.0001 {
+            let is_major_tick = ((tick_value - props.min_value) % graduations.major_tick_interval).abs() < 0.001;
+            let length = thickness * if is_major_tick { 0.35 } else { 0.2 };
+            let position = self.span(bar, 0.0, self.fraction(tick_value));
+            let (x0, y0, x1, y1) = if horizontal {
+                let x = position.x + position.width;
+                let y = bar.y + bar.height + tick_origin;
+                (x, y, x, y + length)
+            } else {
+                let x = bar.x + bar.width + tick_origin;
+                (x, position.y, x + length, position.y)
+            };
+            let mut tick = Path::new();
+            tick.move_to(x0, y0);
+            tick.line_to(x1, y1);
+            canvas.stroke_path(&tick, &tick_paint);
+
+            if is_major_tick && graduations.show_labels {
+                let text = format!("{:.*}", graduations.label_decimals as usize, tick_value);
+                let (lx, ly) = if horizontal { (x1, y1 + 2.0) } else { (x1 + 4.0, y1) };
+                let _ = canvas.fill_text(lx, ly, &text, &tick_label_paint);
+            }
+            tick_value += graduations.minor_tick_interval;
+        }
+
+        // --- DRAW TEXT LABELS ---
+        let mut label_paint = Paint::color(text_color);
+        label_paint.set_font_size(label_font_size);
+        label_paint.set_text_baseline(Baseline::Bottom);
+        let mut value_paint = Paint::color(match self.value {
+            Some(value) if self.zone(value) != Zone::Normal => rgba(self.zone_color(self.zone(value))),
+            _ => text_color,
+        });
+        value_paint.set_font_size(value_font_size);
+        let value_text = match self.value {
+            Some(value) => format!("{:.*} {}", props.value_decimals as usize, value, props.unit),
+            None => format!("-- {}", props.unit),
+        };
+        if horizontal {
+            label_paint.set_text_align(Align::Left);
+            let _ = canvas.fill_text(rect.x, bar.y - value_font_size * 0.1, &props.label, &label_paint);
+            value_paint.set_text_align(Align::Right);
+            value_paint.set_text_baseline(Baseline::Bottom);
+            let _ = canvas.fill_text(rect.x + rect.width, bar.y - value_font_size * 0.1, &value_text, &value_paint);
+        } else {
+            label_paint.set_text_align(Align::Center);
+            let _ = canvas.fill_text(rect.center_x(), bar.y - label_font_size * 0.2, &props.label, &label_paint);
+            value_paint.set_text_align(Align::Center);
+            value_paint.set_text_baseline(Baseline::Top);
+            let _ = canvas.fill_text(rect.center_x(), bar.y + bar.height + value_font_size * 0.2, &value_text, &value_paint);
+        }
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        match self.props.orientation {
+            BarOrientation::Horizontal => WidgetGeometry::new(0.0, 0.0, 300.0, BAR_GAUGE_DESIGN_REFERENCE_THICKNESS),
+            BarOrientation::Vertical => WidgetGeometry::new(0.0, 0.0, BAR_GAUGE_DESIGN_REFERENCE_THICKNESS, 300.0),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::telemetry::health::SourceHealth;
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::time::Duration;
+
+const COLUMNS: [&str; 7] = ["Source", "Rate", "Jitter", "Age", "Latency", "Samples", "Failures"];
+
+/// A table of per-source delivery statistics, for the diagnostics page
+pub struct DiagnosticsPanel {
+    theme: Theme,
+}
+
+impl DiagnosticsPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+
+    fn row(health: &SourceHealth, name: &str, now: Duration) -> [String; 7] {
+        let ms = |v: Option<f32>| v.map(|v| format!("{:.1} ms", v)).unwrap_or_else(|| "-".to_string());
+        [
+            name.to_string(),
+            health.rate_hz().map(|r| format!("{:.1} Hz", r)).unwrap_or_else(|| "-".to_string()),
+            ms(health.jitter_ms()),
+            ms(health.age(now).map(|a| a.as_secs_f32() * 1000.0)),
+            ms(health.latency_ms()),
+            health.samples.to_string(),
+            health.parse_failures.to_string(),
+        ]
+    }
+}
+
+impl Widget for DiagnosticsPanel {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let Ok(state) = telemetry_state.try_lock() else {
+            return;
+        };
+        let now = state.now();
+        let sources = [
+            (&state.health.racebox, "RaceBox", state.racebox_error.as_ref().map(|(e, _)| format!("{:?}", e))),
+            (&state.health.esp32, "ESP32", state.esp32_error.as_ref().map(|(e, _)| format!("{:?}", e))),
+        ];
+
+        let line_height = rect.height / 8.0;
+        let column_width = rect.width / COLUMNS.len() as f32;
+        let mut paint = Paint::color(Theme::color3(self.theme.text_color));
+        paint.set_font_size(line_height * 0.5);
+        paint.set_text_align(Align::Left);
+        paint.set_text_baseline(Baseline::Middle);
+
+        let mut y = rect.y + line_height * 0.5;
+        for (i, title) in COLUMNS.iter().enumerate() {
+            let _ = canvas.fill_text(rect.x + column_width * i as f32, y, title, &paint);
+        }
+
+        for (health, name, error) in sources {
+            y += line_height;
+            let mut row_paint = paint.clone();
+            if health.stale_for(now).is_some() || error.is_some() {
+                row_paint.set_color(Color::rgb(230, 40, 40));
+            }
+            for (i, cell) in Self::row(health, name, now).iter().enumerate() {
+                let _ = canvas.fill_text(rect.x + column_width * i as f32, y, cell, &row_paint);
+            }
+            if let Some(error) = error {
+                y += line_height;
+                let _ = canvas.fill_text(rect.x + column_width, y, &error, &row_paint);
+            }
+        }
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 800.0, 400.0)
+    }
+}
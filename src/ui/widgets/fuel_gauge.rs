@@ -0,0 +1,69 @@
+use crate::ui::widgets::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::widgets::bar_gauge::*;
+use crate::ui::widgets::gauge::GaugeGraduations;
+use crate::ui::theme::Theme;
+use crate::telemetry::SharedTelemetryState;
+use crate::vehicle::FuelConfig;
+use femtovg::{Canvas, renderer::Renderer};
+use std::time::Duration;
+
+/// Fuel in the tank as a segmented horizontal bar, in warning below the reserve
+pub struct FuelGauge {
+    gauge: BarGauge,
+}
+
+impl FuelGauge {
+    pub fn new(theme: &Theme, fuel: &FuelConfig) -> Self {
+        let props = BarGaugeProps {
+            label: "FUEL".to_string(),
+            unit: "l".to_string(),
+            min_value: 0.0,
+            max_value: fuel.tank_l,
+            danger_zone_start: None,
+            warning_zone_start: None,
+            low_warning_zone_end: (fuel.reserve_l > 0.0).then_some(fuel.reserve_l),
+            graduations: GaugeGraduations {
+                major_tick_interval: 10.0,
+                minor_tick_interval: 5.0,
+                show_labels: true,
+                label_decimals: 0,
+            },
+            orientation: BarOrientation::Horizontal,
+            segments: Some(12),
+            style: BarGaugeStyle {
+                bar_color: [40, 200, 70, 255],
+                warning_color: [255, 176, 0, 255],
+                danger_color: [230, 40, 40, 255],
+                background_color: [60, 60, 60, 255],
+                border_color: [255, 255, 255, 255],
+                border_width: 0.0,
+                tick_width: 1.5,
+            },
+            label_font_size: 16.0,
+            value_font_size: 18.0,
+            value_decimals: 1,
+        };
+        Self {
+            gauge: BarGauge::new(props, theme),
+        }
+    }
+
+    pub fn set_value(&mut self, litres: f32) {
+        self.gauge.set_value(litres);
+    }
+}
+
+impl Widget for FuelGauge {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        self.gauge.render(canvas, rect, telemetry_state);
+    }
+    fn on_theme_change(&mut self, new_theme: &Theme, transition: ThemeTransition) {
+        self.gauge.on_theme_change(new_theme, transition);
+    }
+    fn update(&mut self, dt: Duration) {
+        self.gauge.update(dt);
+    }
+    fn preferred_size(&self, ctx: &LayoutContext) -> WidgetGeometry {
+        self.gauge.preferred_size(ctx)
+    }
+}
@@ -1,5 +1,6 @@
-use femtovg::{Canvas, renderer::Renderer, Paint, Path};
+use femtovg::{Canvas, Color, renderer::Renderer, Paint, Path};
 use crate::telemetry::SharedTelemetryState;
+use crate::telemetry::g_force::TRAIL_SPAN;
 use super::{Widget, WidgetGeometry};
 use crate::ui::theme::Theme;
 use std::f32::consts::PI;
@@ -7,8 +8,16 @@ use crate::ui::widgets::{ThemeTransition, LayoutContext};
 use std::time::Duration;
 
 const DIRECTION_LABELS: [&str; 4] = ["FRONT", "RIGHT", "REAR", "LEFT"];
+/// Combined G, as a share of the friction circle limit, shown amber from here and red from 100%
+const FRICTION_WARNING: f32 = 0.85;
 
-/// A widget that displays G-Force as a moving dot in a circular display
+/// A widget that displays G-Force as a moving dot in a circular display.
+///
+/// Behind the dot are a fading trail of the last couple of seconds and the
+/// session's peak envelope, with the peak in each direction written in the top
+/// left corner. With the friction circle turned on in the settings, the limit
+/// is drawn as a circle and the combined G is shown against it in the bottom
+/// right corner.
 pub struct GForceMeter {
     theme: Theme,
     max_g_force_displayed: f32,
@@ -98,14 +107,62 @@ impl Widget for GForceMeter {
         // Negative X is left, positive X is right
         // Negative Y is forward, positive Y is backward
         let max_g = self.max_g_force_displayed;
-        let scaled_x = (g_force_x / max_g).clamp(-1.0, 1.0);
-        let scaled_y = (g_force_y / max_g).clamp(-1.0, 1.0);
-        
-        let dot_x = center_x + scaled_x * radius;
-        let dot_y = center_y - scaled_y * radius; // Invert Y to match physical coordinate system
-        
-        // Calculate dot size based on Z g-force (scaled between 0.5 and 1.5)
+        let to_meter = |x: f32, y: f32| {
+            (
+                center_x + (x / max_g).clamp(-1.0, 1.0) * radius,
+                center_y - (y / max_g).clamp(-1.0, 1.0) * radius, // Invert Y to match physical coordinate system
+            )
+        };
+        let (dot_x, dot_y) = to_meter(g_force_x, g_force_y);
         let base_dot_size = radius * 0.1;
+
+        // Draw the peak envelope
+        let envelope = state.g_force.envelope();
+        if envelope.len() >= 3 {
+            let mut path = Path::new();
+            for (i, (x, y)) in envelope.into_iter().enumerate() {
+                let (px, py) = to_meter(x, y);
+                if i == 0 {
+                    path.move_to(px, py);
+                } else {
+                    path.line_to(px, py);
+                }
+            }
+            path.close();
+            let [r, g, b] = self.theme.accent_color;
+            canvas.fill_path(&path, &Paint::color(Color::rgba(r, g, b, 30)));
+            let mut paint = Paint::color(Color::rgba(r, g, b, 200));
+            paint.set_line_width(self.theme.line_width * 0.75);
+            paint.set_line_join(femtovg::LineJoin::Round);
+            canvas.stroke_path(&path, &paint);
+        }
+
+        // Draw the friction circle
+        let settings = &state.settings;
+        if settings.friction_circle {
+            let mut path = Path::new();
+            path.circle(center_x, center_y, radius * (settings.friction_limit_g / max_g).min(1.0));
+            let [r, g, b] = self.theme.accent_color;
+            let mut paint = Paint::color(Color::rgba(r, g, b, 160));
+            paint.set_line_width(self.theme.line_width);
+            canvas.stroke_path(&path, &paint);
+        }
+
+        // Draw the trail, fading out with age
+        let now = state.now();
+        let [r, g, b] = self.theme.dot_color;
+        for sample in state.g_force.trail() {
+            let age = now.saturating_sub(sample.time).as_secs_f32() / TRAIL_SPAN.as_secs_f32();
+            if age >= 1.0 {
+                continue;
+            }
+            let (x, y) = to_meter(sample.x, sample.y);
+            let mut path = Path::new();
+            path.circle(x, y, base_dot_size * 0.4);
+            canvas.fill_path(&path, &Paint::color(Color::rgba(r, g, b, ((1.0 - age) * 160.0) as u8)));
+        }
+
+        // Calculate dot size based on Z g-force (scaled between 0.5 and 1.5)
         let scale_factor = 1.0 + (g_force_z / max_g).clamp(-0.5, 0.5);
         let dot_size = base_dot_size * scale_factor;
         
@@ -160,6 +217,38 @@ impl Widget for GForceMeter {
                 &text_paint
             );
         }
+
+        // Draw the session peaks in the top left corner
+        let small_font_size = self.theme.font_size * 0.7;
+        text_paint.set_font_size(small_font_size);
+        text_paint.set_text_align(femtovg::Align::Left);
+        text_paint.set_text_baseline(femtovg::Baseline::Top);
+        let _ = canvas.fill_text(geometry.x, geometry.y, "PEAK", &text_paint);
+        for (i, peak) in state.g_force.peaks().iter().enumerate() {
+            let line_y = geometry.y + small_font_size * 1.2 * (i + 1) as f32;
+            let _ = canvas.fill_text(geometry.x, line_y, format!("{} {:.2}G", DIRECTION_LABELS[i], peak), &text_paint);
+        }
+
+        // Draw the combined G against the friction circle in the bottom right corner
+        if settings.friction_circle {
+            let combined = g_force_x.hypot(g_force_y);
+            let share = combined / settings.friction_limit_g;
+            let color = if share >= 1.0 {
+                Color::rgb(230, 40, 40)
+            } else if share >= FRICTION_WARNING {
+                Color::rgb(255, 176, 0)
+            } else {
+                Theme::color3(self.theme.text_color)
+            };
+            let mut paint = Paint::color(color);
+            paint.set_font_size(self.theme.font_size);
+            paint.set_text_align(femtovg::Align::Right);
+            paint.set_text_baseline(femtovg::Baseline::Bottom);
+            let right = geometry.x + geometry.width;
+            let bottom = geometry.y + geometry.height;
+            let _ = canvas.fill_text(right, bottom, format!("{:.0}%", share * 100.0), &paint);
+            let _ = canvas.fill_text(right, bottom - self.theme.font_size * 1.2, format!("{:.2}G", combined), &paint);
+        }
     }
 
     fn on_theme_change(&mut self, new_theme: &Theme, transition: ThemeTransition) {
@@ -0,0 +1,141 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::{Gear, SharedTelemetryState};
+use super::shift_light::{ShiftLightConfig, ShiftLightMode};
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use crate::vehicle::DrivetrainConfig;
+use std::time::Duration;
+
+/// A downshift is only suggested if the lower gear stays this far below its shift point
+const DOWNSHIFT_MARGIN_RPM: f32 = 500.0;
+const UPSHIFT_COLOR: (u8, u8, u8) = (40, 200, 70);
+const DOWNSHIFT_COLOR: (u8, u8, u8) = (255, 176, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShiftSuggestion {
+    Up,
+    Down,
+}
+
+/// Suggest a shift from forward gear `gear` at `rpm` and `speed_kph`: up once the
+/// gear's shift point is reached, down when the revs are low and the lower gear
+/// would stay clear of its own shift point
+fn shift_suggestion(
+    gear: u8,
+    rpm: f32,
+    speed_kph: f32,
+    drivetrain: &DrivetrainConfig,
+    shift_points: &ShiftLightMode,
+) -> Option<ShiftSuggestion> {
+    if gear < drivetrain.gears() && rpm >= shift_points.thresholds(Some(gear)).shift {
+        return Some(ShiftSuggestion::Up);
+    }
+    if gear > 1
+        && rpm < drivetrain.downshift_rpm
+        && drivetrain.rpm_in_gear(gear - 1, speed_kph)? < shift_points.thresholds(Some(gear - 1)).shift - DOWNSHIFT_MARGIN_RPM
+    {
+        return Some(ShiftSuggestion::Down);
+    }
+    None
+}
+
+/// The selected gear in large type, with an arrow suggesting an up- or downshift.
+///
+/// When the gear sensor isn't reporting, the gear is worked out from the ratio of
+/// engine RPM to road speed and shown dimmed; it is blank while that doesn't
+/// match any gear, e.g. with the clutch in. Shift points are the per-gear shift
+/// RPMs of the shift light for the current drive mode.
+pub struct GearIndicator {
+    theme: Theme,
+    shift_points: ShiftLightConfig,
+}
+
+impl GearIndicator {
+    pub fn new(theme: Theme, shift_points: ShiftLightConfig) -> Self {
+        Self { theme, shift_points }
+    }
+}
+
+impl Widget for GearIndicator {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let (gear, computed, suggestion) = {
+            let Ok(state) = telemetry_state.try_lock() else {
+                return;
+            };
+            let esp32 = &state.latest_esp32_data;
+            let rpm = esp32.rpm.map(|rpm| rpm as f32);
+            let speed_kph = esp32
+                .speed
+                .map(|speed| speed as f32)
+                .or_else(|| state.latest_racebox_data.as_ref().filter(|d| d.fix_ok).map(|d| d.speed_kph));
+            let drivetrain = &state.vehicle.drivetrain;
+            let (gear, computed) = match esp32.gear() {
+                Some(gear) => (Some(gear), false),
+                None => {
+                    let gear = rpm.zip(speed_kph).and_then(|(rpm, speed)| drivetrain.gear_from_rpm(rpm, speed));
+                    (gear.map(Gear::Forward), true)
+                }
+            };
+            let suggestion = match (gear, rpm, speed_kph) {
+                (Some(Gear::Forward(gear)), Some(rpm), Some(speed_kph)) => shift_suggestion(
+                    gear,
+                    rpm,
+                    speed_kph,
+                    drivetrain,
+                    self.shift_points.for_mode(state.get_drive_mode()),
+                ),
+                _ => None,
+            };
+            (gear, computed, suggestion)
+        };
+
+        let [r, g, b] = self.theme.text_color;
+        let text_color = if computed { Color::rgba(r, g, b, 150) } else { Color::rgb(r, g, b) };
+        let mut paint = Paint::color(text_color);
+        paint.set_font_size(rect.height * 0.9);
+        paint.set_text_align(Align::Center);
+        paint.set_text_baseline(Baseline::Middle);
+        let label = gear.map_or("-".to_string(), |gear| gear.to_string());
+        let _ = canvas.fill_text(rect.center_x(), rect.center_y(), label, &paint);
+
+        if computed && gear.is_some() {
+            let mut small = Paint::color(text_color);
+            small.set_font_size(rect.height * 0.12);
+            small.set_text_align(Align::Center);
+            small.set_text_baseline(Baseline::Bottom);
+            let _ = canvas.fill_text(rect.center_x(), rect.y + rect.height, "CALC", &small);
+        }
+
+        let Some(suggestion) = suggestion else {
+            return;
+        };
+        // Arrow to the right of the number, pointing the way to shift
+        let size = rect.height * 0.3;
+        let cx = rect.center_x() + rect.height * 0.55;
+        let cy = rect.center_y();
+        let (dir, (r, g, b)) = match suggestion {
+            ShiftSuggestion::Up => (-1.0, UPSHIFT_COLOR),
+            ShiftSuggestion::Down => (1.0, DOWNSHIFT_COLOR),
+        };
+        let mut arrow = Path::new();
+        arrow.move_to(cx, cy + dir * size * 0.6);
+        arrow.line_to(cx + size * 0.5, cy - dir * size * 0.2);
+        arrow.line_to(cx + size * 0.2, cy - dir * size * 0.2);
+        arrow.line_to(cx + size * 0.2, cy - dir * size * 0.6);
+        arrow.line_to(cx - size * 0.2, cy - dir * size * 0.6);
+        arrow.line_to(cx - size * 0.2, cy - dir * size * 0.2);
+        arrow.line_to(cx - size * 0.5, cy - dir * size * 0.2);
+        arrow.close();
+        canvas.fill_path(&arrow, &Paint::color(Color::rgb(r, g, b)));
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 160.0, 160.0)
+    }
+}
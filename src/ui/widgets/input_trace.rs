@@ -0,0 +1,177 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::telemetry::inputs::InputSample;
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::time::Duration;
+
+/// Brake pressure at the top of the trace
+const BRAKE_FULL_SCALE_BAR: f32 = 80.0;
+/// Steering angle at the top and bottom edges of the trace
+const STEERING_FULL_SCALE_DEG: f32 = 180.0;
+/// A gap between samples longer than this breaks the lines, e.g. while the ESP32 was silent
+const MAX_SAMPLE_GAP: Duration = Duration::from_millis(500);
+const THROTTLE_COLOR: (u8, u8, u8) = (40, 200, 70);
+const BRAKE_COLOR: (u8, u8, u8) = (230, 40, 40);
+
+/// Throttle and brake as overlaid filled plots, with the steering angle as a line
+/// around the middle, scrolling from right to left over the trace length set in
+/// the settings.
+///
+/// Each channel is a single path, with at most one point per pixel column. While
+/// the trace is frozen it shows the inputs up to the moment of the freeze.
+pub struct InputTrace {
+    theme: Theme,
+}
+
+impl InputTrace {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+}
+
+/// Filled plot of `value` (0 to 1) along the bottom of `rect`. Runs of samples
+/// without a value, or with a gap between them, become separate shapes.
+fn filled_plot<'a>(
+    samples: impl Iterator<Item = &'a InputSample>,
+    rect: WidgetGeometry,
+    x_of: impl Fn(Duration) -> f32,
+    value: impl Fn(&InputSample) -> Option<f32>,
+) -> Path {
+    let bottom = rect.y + rect.height;
+    let mut path = Path::new();
+    // x where the current shape starts, x of its last point, and time of the last sample in it
+    let mut run: Option<(f32, f32, Duration)> = None;
+    for sample in samples {
+        let x = x_of(sample.time);
+        let point = value(sample).map(|v| (x, bottom - v.clamp(0.0, 1.0) * rect.height));
+        match (point, run) {
+            (Some((x, y)), Some((start, last_x, last_time)))
+                if sample.time.saturating_sub(last_time) <= MAX_SAMPLE_GAP =>
+            {
+                if x - last_x >= 1.0 {
+                    path.line_to(x, y);
+                    run = Some((start, x, sample.time));
+                } else {
+                    run = Some((start, last_x, sample.time));
+                }
+            }
+            (Some((x, y)), previous) => {
+                if let Some((start, last_x, _)) = previous {
+                    close_shape(&mut path, start, last_x, bottom);
+                }
+                path.move_to(x, bottom);
+                path.line_to(x, y);
+                run = Some((x, x, sample.time));
+            }
+            (None, Some((start, last_x, _))) => {
+                close_shape(&mut path, start, last_x, bottom);
+                run = None;
+            }
+            (None, None) => {}
+        }
+    }
+    if let Some((start, last_x, _)) = run {
+        close_shape(&mut path, start, last_x, bottom);
+    }
+    path
+}
+
+fn close_shape(path: &mut Path, start_x: f32, last_x: f32, bottom: f32) {
+    path.line_to(last_x, bottom);
+    path.line_to(start_x, bottom);
+    path.close();
+}
+
+impl Widget for InputTrace {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let (throttle, brake, steering, frozen) = {
+            let Ok(state) = telemetry_state.try_lock() else {
+                return;
+            };
+            let span = Duration::from_secs_f32(state.settings.trace_seconds);
+            let (end, samples) = state.inputs.view(state.now());
+            let start = end.saturating_sub(span);
+            let x_of = |time: Duration| {
+                rect.x + rect.width * (1.0 - end.saturating_sub(time).as_secs_f32() / span.as_secs_f32())
+            };
+            let visible = || samples.iter().filter(|s| s.time >= start && s.time <= end);
+
+            let throttle = filled_plot(visible(), rect, x_of, |s| s.throttle_pct.map(|pct| pct / 100.0));
+            let brake = filled_plot(visible(), rect, x_of, |s| s.brake_bar.map(|bar| bar / BRAKE_FULL_SCALE_BAR));
+
+            let mut steering = Path::new();
+            let mut last: Option<(f32, Duration)> = None;
+            for sample in visible() {
+                let Some(angle) = sample.steering_deg else {
+                    last = None;
+                    continue;
+                };
+                let x = x_of(sample.time);
+                let y = rect.center_y() + (angle / STEERING_FULL_SCALE_DEG).clamp(-1.0, 1.0) * rect.height / 2.0;
+                match last {
+                    Some((last_x, last_time)) if sample.time.saturating_sub(last_time) <= MAX_SAMPLE_GAP => {
+                        if x - last_x >= 1.0 {
+                            steering.line_to(x, y);
+                            last = Some((x, sample.time));
+                        } else {
+                            last = Some((last_x, sample.time));
+                        }
+                    }
+                    _ => {
+                        steering.move_to(x, y);
+                        last = Some((x, sample.time));
+                    }
+                }
+            }
+            (throttle, brake, steering, state.inputs.is_frozen())
+        };
+
+        let [r, g, b] = self.theme.text_color;
+        let mut background = Path::new();
+        background.rect(rect.x, rect.y, rect.width, rect.height);
+        canvas.fill_path(&background, &Paint::color(Color::rgba(r, g, b, 20)));
+        let mut centre_line = Path::new();
+        centre_line.move_to(rect.x, rect.center_y());
+        centre_line.line_to(rect.x + rect.width, rect.center_y());
+        let mut centre_paint = Paint::color(Color::rgba(r, g, b, 60));
+        centre_paint.set_line_width(1.0);
+        canvas.stroke_path(&centre_line, &centre_paint);
+
+        let (tr, tg, tb) = THROTTLE_COLOR;
+        let (br, bg, bb) = BRAKE_COLOR;
+        canvas.fill_path(&throttle, &Paint::color(Color::rgba(tr, tg, tb, 140)));
+        canvas.fill_path(&brake, &Paint::color(Color::rgba(br, bg, bb, 160)));
+        let mut steering_paint = Paint::color(Color::rgb(r, g, b));
+        steering_paint.set_line_width(2.0);
+        canvas.stroke_path(&steering, &steering_paint);
+
+        // Legend in the top left corner, and the freeze state in the top right
+        let font_size = (rect.height * 0.14).max(10.0);
+        let mut paint = Paint::color(Color::rgb(tr, tg, tb));
+        paint.set_font_size(font_size);
+        paint.set_text_align(Align::Left);
+        paint.set_text_baseline(Baseline::Top);
+        let mut x = rect.x + 4.0;
+        for (label, color) in [("THR", Color::rgb(tr, tg, tb)), ("BRK", Color::rgb(br, bg, bb)), ("STR", Color::rgb(r, g, b))] {
+            paint.set_color(color);
+            let _ = canvas.fill_text(x, rect.y + 2.0, label, &paint);
+            x += font_size * 2.6;
+        }
+        if frozen {
+            paint.set_color(Color::rgb(255, 176, 0));
+            paint.set_text_align(Align::Right);
+            let _ = canvas.fill_text(rect.x + rect.width - 4.0, rect.y + 2.0, "FROZEN", &paint);
+        }
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 600.0, 120.0)
+    }
+}
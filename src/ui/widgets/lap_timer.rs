@@ -0,0 +1,213 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::timing::lap_timer::{format_delta, format_lap_time};
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::time::Duration;
+
+/// How long the time of a lap just completed replaces the running time
+const LAP_FLASH: Duration = Duration::from_secs(4);
+/// The lap time flash alternates colour this often
+const FLASH_PERIOD_MS: u128 = 250;
+/// Delta at either end of the delta bar
+const DELTA_FULL_SCALE_S: f64 = 2.0;
+/// Sector times read back from the lap history are rounded, so allow this much when comparing
+const SECTOR_TOLERANCE_S: f64 = 1e-6;
+const PERSONAL_BEST_COLOR: (u8, u8, u8) = (170, 60, 220);
+const SESSION_BEST_COLOR: (u8, u8, u8) = (40, 200, 70);
+const SLOWER_COLOR: (u8, u8, u8) = (255, 176, 0);
+const LOSING_COLOR: (u8, u8, u8) = (230, 40, 40);
+
+/// How a sector compares to the best times for it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SectorStatus {
+    /// Not timed yet
+    Pending,
+    /// As fast as any stored lap at this track
+    PersonalBest,
+    /// Fastest this session
+    SessionBest,
+    Slower,
+}
+
+/// Everything the widget shows, read from the lap timer in one go
+struct LapTimes {
+    lap_count: usize,
+    current: Option<Duration>,
+    last: Option<Duration>,
+    best: Option<Duration>,
+    /// Time of the lap just completed, whether it is the session best, and
+    /// how long ago it was completed
+    flash: Option<(Duration, bool, Duration)>,
+    delta_s: Option<f64>,
+    sectors: Vec<SectorStatus>,
+}
+
+fn rgb((r, g, b): (u8, u8, u8)) -> Color {
+    Color::rgb(r, g, b)
+}
+
+/// Lap timing for track days: the running lap time, completed laps, last and
+/// best lap, a bar showing the live delta to the reference lap and a box per
+/// sector.
+///
+/// Sectors are purple when they match the fastest time stored for the track,
+/// green when they are the fastest this session and amber otherwise. For a few
+/// seconds after crossing the line, the time of the lap just completed flashes
+/// in place of the running time, with its sectors below.
+pub struct LapTimerDisplay {
+    theme: Theme,
+}
+
+impl LapTimerDisplay {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl Widget for LapTimerDisplay {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let times = {
+            let Ok(state) = telemetry_state.try_lock() else {
+                return;
+            };
+            let Some(timer) = state.lap_timer.as_ref() else {
+                return;
+            };
+            let last_lap = timer.last_lap();
+            let best_lap = timer.best_lap();
+            let since_crossing = timer.crossings().last().map(|at| state.now().saturating_sub(*at));
+            let flash = match (last_lap, since_crossing) {
+                (Some(lap), Some(age)) if age < LAP_FLASH => {
+                    Some((lap.time, !lap.pit && best_lap.is_some_and(|best| best.number == lap.number), age))
+                }
+                _ => None,
+            };
+            // While the lap time flashes, show the sectors of the lap it belongs to
+            let sectors = match (flash, last_lap) {
+                (Some(_), Some(lap)) => &lap.sectors[..],
+                _ => timer.current_sectors(),
+            };
+            let personal_bests = match &state.active_track {
+                Some(track) => state.lap_history.best_sectors(&track.name, sectors.len()),
+                None => vec![None; sectors.len()],
+            };
+            let sectors = sectors
+                .iter()
+                .zip(timer.best_sectors())
+                .zip(personal_bests)
+                .map(|((time, session_best), personal_best)| match time {
+                    None => SectorStatus::Pending,
+                    Some(time) if personal_best.is_some_and(|pb| time.as_secs_f64() <= pb + SECTOR_TOLERANCE_S) => {
+                        SectorStatus::PersonalBest
+                    }
+                    Some(time) if session_best.is_some_and(|best| *time <= best) => SectorStatus::SessionBest,
+                    Some(_) => SectorStatus::Slower,
+                })
+                .collect();
+            LapTimes {
+                lap_count: timer.lap_count(),
+                current: timer.current_lap_time(),
+                last: last_lap.map(|lap| lap.time),
+                best: best_lap.map(|lap| lap.time),
+                flash,
+                delta_s: state.lap_delta.delta_s(),
+                sectors,
+            }
+        };
+
+        let [r, g, b] = self.theme.text_color;
+        let text_color = Color::rgb(r, g, b);
+        let dim_color = Color::rgba(r, g, b, 60);
+        let fmt = |time: Option<Duration>| time.map(format_lap_time).unwrap_or_else(|| "-:--.---".to_string());
+        let mut paint = Paint::color(text_color);
+        paint.set_text_baseline(Baseline::Top);
+
+        // Completed laps, top left
+        paint.set_font_size(rect.height * 0.13);
+        paint.set_text_align(Align::Left);
+        let _ = canvas.fill_text(rect.x, rect.y, format!("LAPS {}", times.lap_count), &paint);
+
+        // Running lap time, or the lap just completed flashing
+        let (time, color) = match times.flash {
+            Some((time, is_best, age)) => {
+                let highlight = if is_best { SESSION_BEST_COLOR } else { SLOWER_COLOR };
+                let on = (age.as_millis() / FLASH_PERIOD_MS).is_multiple_of(2);
+                (Some(time), if on { rgb(highlight) } else { text_color })
+            }
+            None => (times.current, text_color),
+        };
+        paint.set_color(color);
+        paint.set_font_size(rect.height * 0.32);
+        let _ = canvas.fill_text(rect.x, rect.y + rect.height * 0.15, fmt(time), &paint);
+
+        // Live delta, right of the lap time
+        let delta_color = match times.delta_s {
+            Some(delta) if delta < 0.0 => rgb(SESSION_BEST_COLOR),
+            Some(delta) if delta > 0.0 => rgb(LOSING_COLOR),
+            _ => text_color,
+        };
+        paint.set_color(delta_color);
+        paint.set_font_size(rect.height * 0.18);
+        paint.set_text_align(Align::Right);
+        let delta_text = times.delta_s.map(format_delta).unwrap_or_else(|| "-".to_string());
+        let _ = canvas.fill_text(rect.x + rect.width, rect.y + rect.height * 0.22, delta_text, &paint);
+
+        // Delta bar, filling from the centre: left and green when gaining, right and red when losing
+        let bar_y = rect.y + rect.height * 0.52;
+        let bar_height = rect.height * 0.12;
+        let mut bar = Path::new();
+        bar.rect(rect.x, bar_y, rect.width, bar_height);
+        canvas.fill_path(&bar, &Paint::color(Color::rgba(r, g, b, 20)));
+        if let Some(delta) = times.delta_s {
+            let fraction = (delta / DELTA_FULL_SCALE_S).clamp(-1.0, 1.0) as f32;
+            let fill_width = fraction * rect.width / 2.0;
+            let mut fill = Path::new();
+            fill.rect(rect.center_x().min(rect.center_x() + fill_width), bar_y, fill_width.abs(), bar_height);
+            canvas.fill_path(&fill, &Paint::color(delta_color));
+        }
+        let mut centre = Path::new();
+        centre.move_to(rect.center_x(), bar_y - 2.0);
+        centre.line_to(rect.center_x(), bar_y + bar_height + 2.0);
+        let mut centre_paint = Paint::color(text_color);
+        centre_paint.set_line_width(2.0);
+        canvas.stroke_path(&centre, &centre_paint);
+
+        // One box per sector
+        if !times.sectors.is_empty() {
+            let gap = 4.0;
+            let box_y = rect.y + rect.height * 0.69;
+            let box_height = rect.height * 0.08;
+            let box_width = (rect.width - gap * (times.sectors.len() - 1) as f32) / times.sectors.len() as f32;
+            for (i, status) in times.sectors.iter().enumerate() {
+                let color = match status {
+                    SectorStatus::Pending => dim_color,
+                    SectorStatus::PersonalBest => rgb(PERSONAL_BEST_COLOR),
+                    SectorStatus::SessionBest => rgb(SESSION_BEST_COLOR),
+                    SectorStatus::Slower => rgb(SLOWER_COLOR),
+                };
+                let mut sector = Path::new();
+                sector.rect(rect.x + i as f32 * (box_width + gap), box_y, box_width, box_height);
+                canvas.fill_path(&sector, &Paint::color(color));
+            }
+        }
+
+        // Last and best lap along the bottom
+        paint.set_color(text_color);
+        paint.set_font_size(rect.height * 0.13);
+        paint.set_text_align(Align::Left);
+        let _ = canvas.fill_text(rect.x, rect.y + rect.height * 0.84, format!("LAST {}", fmt(times.last)), &paint);
+        paint.set_text_align(Align::Right);
+        let _ = canvas.fill_text(rect.x + rect.width, rect.y + rect.height * 0.84, format!("BEST {}", fmt(times.best)), &paint);
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 300.0, 140.0)
+    }
+}
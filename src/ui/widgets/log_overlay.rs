@@ -0,0 +1,77 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::logging::{self, ESP32_NAMESPACE, RACEBOX_NAMESPACE};
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use log::Level;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Namespaces whose problems are worth showing to the driver
+const WATCHED_NAMESPACES: [&str; 2] = [RACEBOX_NAMESPACE, ESP32_NAMESPACE];
+const MAX_LINES: usize = 4;
+/// Entries older than this are no longer shown
+const MAX_AGE: Duration = Duration::from_secs(30);
+
+/// An overlay listing the latest warnings and errors from the RaceBox and ESP32 sources
+pub struct LogOverlay {
+    theme: Theme,
+}
+
+impl LogOverlay {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl Widget for LogOverlay {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, _telemetry_state: &SharedTelemetryState) {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let entries: Vec<_> = logging::recent_entries(Level::Warn, &WATCHED_NAMESPACES, MAX_LINES)
+            .into_iter()
+            .filter(|e| now_ms.saturating_sub(e.timestamp_ms) <= MAX_AGE.as_millis() as u64)
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+
+        let line_height = rect.height / MAX_LINES as f32;
+        let font_size = line_height * 0.6;
+        let box_height = line_height * entries.len() as f32;
+        let box_y = rect.y + rect.height - box_height;
+
+        let mut path = Path::new();
+        path.rounded_rect(rect.x, box_y, rect.width, box_height, line_height * 0.2);
+        let bg = self.theme.background_color;
+        canvas.fill_path(&path, &Paint::color(Color::rgba(bg[0], bg[1], bg[2], 200)));
+
+        for (i, entry) in entries.iter().enumerate() {
+            let color = match entry.level {
+                Level::Error => Color::rgb(230, 40, 40),
+                _ => Color::rgb(255, 170, 0),
+            };
+            let source = entry.target.rsplit("::").next().unwrap_or(&entry.target);
+            let age = now_ms.saturating_sub(entry.timestamp_ms) / 1000;
+            let text = format!("{} {}: {} ({}s)", entry.level, source, entry.message, age);
+
+            let mut paint = Paint::color(color);
+            paint.set_font_size(font_size);
+            paint.set_text_align(Align::Left);
+            paint.set_text_baseline(Baseline::Middle);
+            let y = box_y + line_height * (i as f32 + 0.5);
+            let _ = canvas.fill_text(rect.x + line_height * 0.3, y, &text, &paint);
+        }
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 600.0, 120.0)
+    }
+}
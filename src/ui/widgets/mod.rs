@@ -8,6 +8,20 @@ pub mod g_force_meter;
 pub mod gauge;
 pub mod turbo_pressure_gauge;
 pub mod rpm_gauge;
+pub mod log_overlay;
+pub mod diagnostics_panel;
+pub mod alert_banner;
+pub mod telltales;
+pub mod shift_light;
+pub mod gear_indicator;
+pub mod speedometer;
+pub mod bar_gauge;
+pub mod fuel_gauge;
+pub mod temperature_gauge;
+pub mod tyre_panel;
+pub mod input_trace;
+pub mod lap_timer;
+pub mod track_map;
 
 /// Defines the position and size of a widget
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,284 @@
+use femtovg::{Canvas, Color, Paint, Path, renderer::Renderer};
+use serde::Deserialize;
+use crate::telemetry::{DriveMode, SharedTelemetryState};
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path as FsPath, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+const SHIFT_LIGHT_FILE: &str = "shift_light.yml";
+/// Time constant of the smoothing applied to the rate of RPM rise
+const RPM_RATE_SMOOTHING_S: f32 = 0.2;
+/// Opacity of an unlit segment
+const OFF_ALPHA: u8 = 40;
+
+#[derive(Error, Debug)]
+pub enum ShiftLightError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid shift light config: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Invalid shift light config: {0}")]
+    Invalid(String),
+}
+
+/// RPM at which the first segment lights and at which to shift
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ShiftThresholds {
+    pub start: f32,
+    pub shift: f32,
+}
+
+/// Shift light settings for one drive mode
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShiftLightMode {
+    pub segments: usize,
+    /// Segment colours from the first segment to the last, spread evenly over the bar
+    pub colors: Vec<[u8; 3]>,
+    /// Colour of the whole bar at the shift point
+    pub flash_color: [u8; 3],
+    pub flash_hz: f32,
+    /// Thresholds for gears without their own, and for neutral and reverse
+    pub thresholds: ShiftThresholds,
+    /// Thresholds per gear, by gear number
+    #[serde(default)]
+    pub gears: BTreeMap<u8, ShiftThresholds>,
+    /// Bring the thresholds forward by the RPM the engine gains in this time, so
+    /// the light allows for the driver's reaction time when the revs rise quickly
+    #[serde(default)]
+    pub reaction_time_s: Option<f32>,
+}
+
+impl ShiftLightMode {
+    pub fn thresholds(&self, gear: Option<u8>) -> ShiftThresholds {
+        gear.and_then(|gear| self.gears.get(&gear)).copied().unwrap_or(self.thresholds)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.segments == 0 {
+            return Err("segments must be at least 1".to_string());
+        }
+        if self.colors.is_empty() {
+            return Err("at least one colour is needed".to_string());
+        }
+        let all = std::iter::once(&self.thresholds).chain(self.gears.values());
+        if all.into_iter().any(|t| t.start >= t.shift) {
+            return Err("every start RPM must be below its shift RPM".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Shift light settings per drive mode, loaded from `assets/shift_light.yml`.
+///
+/// Example YAML format:
+///
+/// ```yaml
+/// road:
+///   segments: 8
+///   colors: [[40, 200, 70], [255, 176, 0], [230, 40, 40]]
+///   flash_color: [40, 110, 255]
+///   flash_hz: 4
+///   thresholds: { start: 4500, shift: 6000 }
+/// track:
+///   segments: 12
+///   colors: [[40, 200, 70], [255, 176, 0], [230, 40, 40]]
+///   flash_color: [40, 110, 255]
+///   flash_hz: 8
+///   thresholds: { start: 5000, shift: 6500 }
+///   gears:
+///     1: { start: 5200, shift: 6300 }
+///   reaction_time_s: 0.1
+/// ```
+///
+/// `gears` and `reaction_time_s` are optional.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShiftLightConfig {
+    pub road: ShiftLightMode,
+    pub track: ShiftLightMode,
+}
+
+impl Default for ShiftLightConfig {
+    fn default() -> Self {
+        let mode = ShiftLightMode {
+            segments: 10,
+            colors: vec![[40, 200, 70], [255, 176, 0], [230, 40, 40]],
+            flash_color: [40, 110, 255],
+            flash_hz: 6.0,
+            thresholds: ShiftThresholds { start: 4500.0, shift: 6200.0 },
+            gears: BTreeMap::new(),
+            reaction_time_s: None,
+        };
+        Self {
+            road: mode.clone(),
+            track: mode,
+        }
+    }
+}
+
+impl ShiftLightConfig {
+    /// `assets/shift_light.yml` next to the executable, or in the current directory
+    pub fn default_path() -> PathBuf {
+        if let Ok(exe_path) = env::current_exe()
+            && let Some(exe_dir) = exe_path.parent()
+        {
+            let path = exe_dir.join("assets").join(SHIFT_LIGHT_FILE);
+            if path.exists() {
+                return path;
+            }
+        }
+        FsPath::new("assets").join(SHIFT_LIGHT_FILE)
+    }
+
+    pub fn load<P: AsRef<FsPath>>(path: P) -> Result<Self, ShiftLightError> {
+        let yaml = fs::read_to_string(path)?;
+        let config: Self = serde_yaml::from_str(&yaml)?;
+        config.road.validate().map_err(|e| ShiftLightError::Invalid(format!("road: {}", e)))?;
+        config.track.validate().map_err(|e| ShiftLightError::Invalid(format!("track: {}", e)))?;
+        Ok(config)
+    }
+
+    /// Load the default config, falling back to built-in settings
+    pub fn load_default() -> Self {
+        let path = Self::default_path();
+        match Self::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                crate::ui_log!(log::Level::Warn, "Shift light config {} not loaded: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn for_mode(&self, drive_mode: DriveMode) -> &ShiftLightMode {
+        match drive_mode {
+            DriveMode::Road => &self.road,
+            DriveMode::Track => &self.track,
+        }
+    }
+}
+
+/// A bar of LED segments that light one by one as the revs approach the shift
+/// point for the current gear, and flash together once it is reached.
+///
+/// With `reaction_time_s` set, both thresholds come down by the RPM gained over
+/// that time at the current rate of rise, so the light comes on earlier in the
+/// low gears where the revs climb fastest.
+pub struct ShiftLight {
+    config: ShiftLightConfig,
+    drive_mode: DriveMode,
+    rpm: Option<f32>,
+    gear: Option<u8>,
+    /// Smoothed rate of RPM rise, in RPM per second
+    rpm_rate: f32,
+    /// Time since the previous RPM value was set
+    since_last_value: Duration,
+    /// Time since the widget was created, for the flash phase
+    elapsed: Duration,
+}
+
+impl ShiftLight {
+    pub fn new(config: ShiftLightConfig) -> Self {
+        Self {
+            config,
+            drive_mode: DriveMode::Road,
+            rpm: None,
+            gear: None,
+            rpm_rate: 0.0,
+            since_last_value: Duration::ZERO,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn set_drive_mode(&mut self, drive_mode: DriveMode) {
+        self.drive_mode = drive_mode;
+    }
+
+    /// Feed the latest RPM and gear, once per frame
+    pub fn set_value(&mut self, rpm: Option<f32>, gear: Option<u8>) {
+        let dt = std::mem::take(&mut self.since_last_value).as_secs_f32();
+        match (self.rpm, rpm) {
+            (Some(previous), Some(rpm)) if dt > 0.0 => {
+                let alpha = 1.0 - (-dt / RPM_RATE_SMOOTHING_S).exp();
+                self.rpm_rate += ((rpm - previous) / dt - self.rpm_rate) * alpha;
+            }
+            (_, None) => self.rpm_rate = 0.0,
+            _ => {}
+        }
+        self.rpm = rpm;
+        self.gear = gear;
+    }
+
+    /// Thresholds for the current gear, brought forward for the rate of rise
+    fn effective_thresholds(&self) -> ShiftThresholds {
+        let mode = self.config.for_mode(self.drive_mode);
+        let thresholds = mode.thresholds(self.gear);
+        let lead = mode.reaction_time_s.map_or(0.0, |time| self.rpm_rate.max(0.0) * time);
+        ShiftThresholds {
+            start: thresholds.start - lead,
+            shift: thresholds.shift - lead,
+        }
+    }
+
+    /// Number of segments lit, and whether the bar is at the shift point
+    fn lit_segments(&self) -> (usize, bool) {
+        let Some(rpm) = self.rpm else {
+            return (0, false);
+        };
+        let segments = self.config.for_mode(self.drive_mode).segments;
+        let thresholds = self.effective_thresholds();
+        if rpm >= thresholds.shift {
+            return (segments, true);
+        }
+        if rpm < thresholds.start {
+            return (0, false);
+        }
+        let fraction = (rpm - thresholds.start) / (thresholds.shift - thresholds.start);
+        (((fraction * segments as f32) as usize + 1).min(segments), false)
+    }
+}
+
+impl Widget for ShiftLight {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, _telemetry_state: &SharedTelemetryState) {
+        let mode = self.config.for_mode(self.drive_mode);
+        let (lit, shift) = self.lit_segments();
+        // During the flash the bar alternates between the flash colour and dark
+        let flash_on = ((self.elapsed.as_secs_f32() * mode.flash_hz * 2.0) as u64).is_multiple_of(2);
+
+        let gap = rect.width * 0.1 / mode.segments as f32;
+        let width = (rect.width - gap * (mode.segments - 1) as f32) / mode.segments as f32;
+        let radius = width.min(rect.height) * 0.2;
+        for i in 0..mode.segments {
+            let [r, g, b] = if shift {
+                mode.flash_color
+            } else {
+                mode.colors[i * mode.colors.len() / mode.segments]
+            };
+            let color = if (shift && flash_on) || (!shift && i < lit) {
+                Color::rgb(r, g, b)
+            } else {
+                Color::rgba(r, g, b, OFF_ALPHA)
+            };
+            let mut path = Path::new();
+            path.rounded_rect(rect.x + (width + gap) * i as f32, rect.y, width, rect.height, radius);
+            canvas.fill_path(&path, &Paint::color(color));
+        }
+    }
+
+    fn on_theme_change(&mut self, _new_theme: &Theme, _transition: ThemeTransition) {}
+
+    fn update(&mut self, dt: Duration) {
+        self.elapsed = self.elapsed.saturating_add(dt);
+        self.since_last_value = self.since_last_value.saturating_add(dt);
+    }
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 600.0, 40.0)
+    }
+}
@@ -0,0 +1,178 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::racebox::parser::GnssQuality;
+use crate::settings::{Settings, SpeedSource, SpeedUnit};
+use crate::telemetry::{SharedTelemetryState, SpeedReading};
+use crate::ui::widgets::gauge::*;
+use crate::ui::widgets::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::time::Duration;
+
+/// Bars of the GNSS quality indicator
+const QUALITY_BARS: usize = 3;
+
+/// Road speed as a large digital readout, optionally inside an arc gauge.
+///
+/// Speed, unit and source come from the settings. When the speed is from GNSS,
+/// a small bar indicator shows the quality of the fix.
+pub struct Speedometer {
+    theme: Theme,
+    unit: SpeedUnit,
+    source: SpeedSource,
+    gauge: Option<Gauge>,
+    reading: Option<SpeedReading>,
+}
+
+impl Speedometer {
+    pub fn new(theme: &Theme, settings: &Settings) -> Self {
+        let gauge = settings.speed_arc.then(|| {
+            let max_value = match settings.speed_unit {
+                SpeedUnit::Kph => 300.0,
+                SpeedUnit::Mph => 180.0,
+            };
+            Gauge::new(GaugeProps {
+                label: String::new(),
+                unit: settings.speed_unit.label().to_string(),
+                min_value: 0.0,
+                max_value,
+                danger_zone_start: None,
+                graduations: GaugeGraduations {
+                    major_tick_interval: max_value / 6.0,
+                    minor_tick_interval: max_value / 30.0,
+                    show_labels: true,
+                    label_decimals: 0,
+                },
+                start_angle: -5.0 * std::f32::consts::PI / 4.0,   // 225°
+                end_angle: -std::f32::consts::PI / 4.0,    // -45°
+                radius_ratio: 0.9,
+                center_offset: (0.0, 0.0),
+                tick_style: GaugeTickStyle {
+                    major_tick_width: 3.0,
+                    major_tick_length: 0.15,
+                    minor_tick_width: 1.5,
+                    minor_tick_length: 0.08,
+                    tick_color: [255, 255, 255, 255],
+                    danger_zone_color: [255, 0, 0, 180],
+                },
+                needle: GaugeNeedleStyle {
+                    sprite_path: None,
+                    color: [255, 255, 255, 255],
+                    width: 3.0,
+                    length: 0.8,
+                    pivot: (0.0, 0.0),
+                    shadow: None,
+                },
+                label_position: (0.5, 0.85),
+                unit_position: (0.5, 0.8),
+                label_font_size: 22.0,
+                unit_font_size: 16.0,
+                show_value: false,
+                value_position: (0.5, 0.7),
+                value_font_size: 18.0,
+                value_decimals: 0,
+                background_color: [0, 0, 0, 255],
+                border_color: [255, 255, 255, 255],
+                border_width: 2.0,
+                track: None,
+                clockwise: false,
+            })
+        });
+        Self {
+            theme: theme.clone(),
+            unit: settings.speed_unit,
+            source: settings.speed_source,
+            gauge,
+            reading: None,
+        }
+    }
+
+    pub fn set_value(&mut self, reading: Option<SpeedReading>) {
+        self.reading = reading;
+        if let (Some(gauge), Some(reading)) = (self.gauge.as_mut(), reading) {
+            gauge.set_value(self.unit.convert_kph(reading.kph));
+        }
+    }
+
+    /// Quality to show, while the speed is from GNSS or GNSS was asked for
+    fn gnss_quality(&self) -> Option<GnssQuality> {
+        match self.reading {
+            Some(reading) => reading.gnss_quality,
+            None if self.source == SpeedSource::Gnss => Some(GnssQuality::NoFix),
+            None => None,
+        }
+    }
+}
+
+impl Widget for Speedometer {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let (digits_size, unit_size) = match &self.gauge {
+            Some(gauge) => {
+                gauge.render(canvas, rect, telemetry_state);
+                (rect.width.min(rect.height) * 0.22, 0.0)
+            }
+            None => (rect.height * 0.7, rect.height * 0.2),
+        };
+
+        // White on the black face of the arc gauge, like its ticks
+        let text_color = match self.gauge {
+            Some(_) => Color::rgb(255, 255, 255),
+            None => Theme::color3(self.theme.text_color),
+        };
+        let speed = self.reading.map_or("--".to_string(), |r| format!("{:.0}", self.unit.convert_kph(r.kph)));
+        let mut paint = Paint::color(text_color);
+        paint.set_font_size(digits_size);
+        paint.set_text_align(Align::Center);
+        paint.set_text_baseline(Baseline::Middle);
+        let _ = canvas.fill_text(rect.center_x(), rect.center_y(), speed, &paint);
+
+        // The arc gauge draws its own unit label
+        if unit_size > 0.0 {
+            let mut unit = Paint::color(text_color);
+            unit.set_font_size(unit_size);
+            unit.set_text_align(Align::Center);
+            unit.set_text_baseline(Baseline::Bottom);
+            let _ = canvas.fill_text(rect.center_x(), rect.y + rect.height, self.unit.label(), &unit);
+        }
+
+        let Some(quality) = self.gnss_quality() else {
+            return;
+        };
+        let lit = match quality {
+            GnssQuality::NoFix => 0,
+            GnssQuality::Poor => 1,
+            GnssQuality::Fair => 2,
+            GnssQuality::Good => 3,
+        };
+        let color = match quality {
+            GnssQuality::NoFix | GnssQuality::Poor => Color::rgb(230, 40, 40),
+            GnssQuality::Fair => Color::rgb(255, 176, 0),
+            GnssQuality::Good => Color::rgb(40, 200, 70),
+        };
+        // Signal-strength style bars in the top right corner
+        let bar_width = rect.height * 0.06;
+        let max_height = rect.height * 0.2;
+        for i in 0..QUALITY_BARS {
+            let height = max_height * (i + 1) as f32 / QUALITY_BARS as f32;
+            let x = rect.x + rect.width - (QUALITY_BARS - i) as f32 * bar_width * 1.6;
+            let mut bar = Path::new();
+            bar.rect(x, rect.y + max_height - height, bar_width, height);
+            let fill = if i < lit { color } else { Color::rgba(128, 128, 128, 80) };
+            canvas.fill_path(&bar, &Paint::color(fill));
+        }
+        let mut label = Paint::color(if lit == 0 { color } else { text_color });
+        label.set_font_size(max_height * 0.7);
+        label.set_text_align(Align::Right);
+        label.set_text_baseline(Baseline::Bottom);
+        let label_x = rect.x + rect.width - QUALITY_BARS as f32 * bar_width * 1.6 - bar_width;
+        let _ = canvas.fill_text(label_x, rect.y + max_height, "GPS", &label);
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 200.0, 200.0)
+    }
+}
@@ -0,0 +1,234 @@
+use femtovg::{Align, Baseline, Canvas, Color, LineCap, LineJoin, Paint, Path, Solidity, renderer::Renderer};
+use crate::telemetry::{SharedTelemetryState, StatusFlags};
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+/// How long every lamp stays lit at startup, as the bulb check of a real cluster
+const BULB_CHECK: Duration = Duration::from_secs(2);
+/// Opacity of a lamp that is off, so its position stays readable
+const OFF_ALPHA: u8 = 28;
+
+/// Lamp colours by severity, following ISO 2575
+const RED: (u8, u8, u8) = (230, 40, 40);
+const AMBER: (u8, u8, u8) = (255, 176, 0);
+const GREEN: (u8, u8, u8) = (40, 200, 70);
+const BLUE: (u8, u8, u8) = (40, 110, 255);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symbol {
+    LeftTurn,
+    HighBeam,
+    EngineCheck,
+    Abs,
+    Airbag,
+    ParkingBrake,
+    RightTurn,
+}
+
+impl Symbol {
+    /// Cluster order, left to right
+    const ALL: [Symbol; 7] = [
+        Symbol::LeftTurn,
+        Symbol::HighBeam,
+        Symbol::EngineCheck,
+        Symbol::Abs,
+        Symbol::Airbag,
+        Symbol::ParkingBrake,
+        Symbol::RightTurn,
+    ];
+
+    fn color(self) -> (u8, u8, u8) {
+        match self {
+            Symbol::LeftTurn | Symbol::RightTurn => GREEN,
+            Symbol::HighBeam => BLUE,
+            Symbol::EngineCheck | Symbol::Abs => AMBER,
+            Symbol::Airbag | Symbol::ParkingBrake => RED,
+        }
+    }
+
+    fn is_lit(self, flags: &StatusFlags) -> bool {
+        match self {
+            Symbol::LeftTurn => flags.left_turn,
+            Symbol::HighBeam => flags.high_beam,
+            Symbol::EngineCheck => flags.mil,
+            Symbol::Abs => flags.abs_warning,
+            Symbol::Airbag => flags.airbag_warning,
+            Symbol::ParkingBrake => flags.parking_brake,
+            Symbol::RightTurn => flags.right_turn,
+        }
+    }
+}
+
+/// Warning and indicator lamps driven by the ESP32's status flags.
+///
+/// The turn signal flags follow the flasher circuit, so the arrows blink in
+/// step with the relay rather than on a timer of their own. Lamps are drawn as
+/// vector symbols in the style of ISO 2575, coloured by severity. Every lamp is
+/// lit for the first two seconds after startup, like the bulb check of a real
+/// cluster.
+pub struct Telltales {
+    theme: Theme,
+    elapsed: Duration,
+}
+
+impl Telltales {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn bulb_check(&self) -> bool {
+        self.elapsed < BULB_CHECK
+    }
+}
+
+impl Widget for Telltales {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let flags = match telemetry_state.try_lock() {
+            Ok(state) => state.latest_esp32_data.status_flags.unwrap_or_default(),
+            Err(_) => return,
+        };
+
+        let slot = (rect.width / Symbol::ALL.len() as f32).min(rect.height);
+        let gap = (rect.width - slot * Symbol::ALL.len() as f32) / Symbol::ALL.len() as f32;
+        let size = slot * 0.8;
+        for (i, symbol) in Symbol::ALL.into_iter().enumerate() {
+            let cx = rect.x + gap / 2.0 + (slot + gap) * i as f32 + slot / 2.0;
+            let cy = rect.center_y();
+            let color = if self.bulb_check() || symbol.is_lit(&flags) {
+                let (r, g, b) = symbol.color();
+                Color::rgb(r, g, b)
+            } else {
+                let [r, g, b] = self.theme.text_color;
+                Color::rgba(r, g, b, OFF_ALPHA)
+            };
+            draw_symbol(canvas, symbol, cx, cy, size, color);
+        }
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, dt: Duration) {
+        self.elapsed = self.elapsed.saturating_add(dt);
+    }
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 560.0, 80.0)
+    }
+}
+
+/// Draw `symbol` centred on (`cx`, `cy`) within a square of side `size`
+fn draw_symbol<R: Renderer>(canvas: &mut Canvas<R>, symbol: Symbol, cx: f32, cy: f32, size: f32, color: Color) {
+    let s = size / 2.0;
+    let mut stroke = Paint::color(color);
+    stroke.set_line_width(size * 0.07);
+    stroke.set_line_cap(LineCap::Round);
+    stroke.set_line_join(LineJoin::Round);
+    let fill = Paint::color(color);
+
+    match symbol {
+        Symbol::LeftTurn | Symbol::RightTurn => {
+            let dir = if symbol == Symbol::LeftTurn { -1.0 } else { 1.0 };
+            let mut path = Path::new();
+            path.move_to(cx + dir * s * 0.9, cy);
+            path.line_to(cx + dir * s * 0.1, cy - s * 0.7);
+            path.line_to(cx + dir * s * 0.1, cy - s * 0.3);
+            path.line_to(cx - dir * s * 0.8, cy - s * 0.3);
+            path.line_to(cx - dir * s * 0.8, cy + s * 0.3);
+            path.line_to(cx + dir * s * 0.1, cy + s * 0.3);
+            path.line_to(cx + dir * s * 0.1, cy + s * 0.7);
+            path.close();
+            canvas.fill_path(&path, &fill);
+        }
+        Symbol::HighBeam => {
+            // Lamp lens facing left, with straight beams
+            let mut lens = Path::new();
+            lens.move_to(cx + s * 0.1, cy - s * 0.55);
+            lens.bezier_to(cx + s * 0.95, cy - s * 0.55, cx + s * 0.95, cy + s * 0.55, cx + s * 0.1, cy + s * 0.55);
+            lens.close();
+            canvas.fill_path(&lens, &fill);
+            let mut beams = Path::new();
+            for i in 0..4 {
+                let y = cy - s * 0.45 + s * 0.3 * i as f32;
+                beams.move_to(cx - s * 0.15, y);
+                beams.line_to(cx - s * 0.85, y);
+            }
+            canvas.stroke_path(&beams, &stroke);
+        }
+        Symbol::EngineCheck => {
+            // Engine block outline, with the valve cover on top, a fan on the left
+            // and the exhaust on the right
+            let mut path = Path::new();
+            path.move_to(cx - s * 0.55, cy - s * 0.3);
+            path.line_to(cx - s * 0.2, cy - s * 0.3);
+            path.line_to(cx - s * 0.2, cy - s * 0.5);
+            path.line_to(cx + s * 0.25, cy - s * 0.5);
+            path.line_to(cx + s * 0.25, cy - s * 0.3);
+            path.line_to(cx + s * 0.5, cy - s * 0.3);
+            path.line_to(cx + s * 0.5, cy - s * 0.1);
+            path.line_to(cx + s * 0.8, cy - s * 0.3);
+            path.line_to(cx + s * 0.8, cy + s * 0.4);
+            path.line_to(cx + s * 0.5, cy + s * 0.2);
+            path.line_to(cx + s * 0.5, cy + s * 0.45);
+            path.line_to(cx - s * 0.35, cy + s * 0.45);
+            path.line_to(cx - s * 0.55, cy + s * 0.2);
+            path.close();
+            path.move_to(cx - s * 0.55, cy);
+            path.line_to(cx - s * 0.85, cy);
+            path.move_to(cx - s * 0.85, cy - s * 0.25);
+            path.line_to(cx - s * 0.85, cy + s * 0.25);
+            canvas.stroke_path(&path, &stroke);
+        }
+        Symbol::Abs => {
+            draw_ringed_circle(canvas, cx, cy, s, &stroke);
+            draw_label(canvas, "ABS", cx, cy, size * 0.3, color);
+        }
+        Symbol::ParkingBrake => {
+            draw_ringed_circle(canvas, cx, cy, s, &stroke);
+            draw_label(canvas, "P", cx, cy, size * 0.45, color);
+        }
+        Symbol::Airbag => {
+            // Seated occupant with an inflated bag in front
+            let mut head = Path::new();
+            head.circle(cx - s * 0.35, cy - s * 0.6, s * 0.16);
+            canvas.fill_path(&head, &fill);
+            let mut body = Path::new();
+            body.move_to(cx - s * 0.45, cy - s * 0.3);
+            body.line_to(cx - s * 0.55, cy + s * 0.3);
+            body.line_to(cx - s * 0.05, cy + s * 0.3);
+            body.line_to(cx + s * 0.05, cy + s * 0.8);
+            body.move_to(cx - s * 0.45, cy - s * 0.1);
+            body.line_to(cx - s * 0.05, cy - s * 0.05);
+            canvas.stroke_path(&body, &stroke);
+            let mut bag = Path::new();
+            bag.circle(cx + s * 0.45, cy - s * 0.25, s * 0.35);
+            canvas.fill_path(&bag, &fill);
+        }
+    }
+}
+
+/// A circle between two outer arcs, the frame of the brake system symbols
+fn draw_ringed_circle<R: Renderer>(canvas: &mut Canvas<R>, cx: f32, cy: f32, s: f32, stroke: &Paint) {
+    let mut path = Path::new();
+    path.circle(cx, cy, s * 0.6);
+    canvas.stroke_path(&path, stroke);
+    for side in [0.0, PI] {
+        let mut arc = Path::new();
+        arc.arc(cx, cy, s * 0.85, side - PI / 4.0, side + PI / 4.0, Solidity::Hole);
+        canvas.stroke_path(&arc, stroke);
+    }
+}
+
+fn draw_label<R: Renderer>(canvas: &mut Canvas<R>, text: &str, cx: f32, cy: f32, font_size: f32, color: Color) {
+    let mut paint = Paint::color(color);
+    paint.set_font_size(font_size);
+    paint.set_text_align(Align::Center);
+    paint.set_text_baseline(Baseline::Middle);
+    let _ = canvas.fill_text(cx, cy, text, &paint);
+}
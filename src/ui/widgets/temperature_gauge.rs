@@ -0,0 +1,79 @@
+use crate::ui::widgets::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::widgets::bar_gauge::*;
+use crate::ui::widgets::gauge::GaugeGraduations;
+use crate::ui::theme::Theme;
+use crate::telemetry::SharedTelemetryState;
+use femtovg::{Canvas, renderer::Renderer};
+use std::time::Duration;
+
+/// A fluid temperature as a vertical bar: in warning while still cold and when
+/// running hot, in danger when overheating
+pub struct TemperatureGauge {
+    gauge: BarGauge,
+}
+
+impl TemperatureGauge {
+    /// Engine coolant, normally 85–95 °C
+    pub fn coolant(theme: &Theme) -> Self {
+        Self::new(theme, "WATER", 40.0, 130.0, 60.0, 105.0, 115.0)
+    }
+
+    /// Engine oil, normally 90–110 °C
+    pub fn oil(theme: &Theme) -> Self {
+        Self::new(theme, "OIL", 40.0, 160.0, 70.0, 125.0, 140.0)
+    }
+
+    fn new(theme: &Theme, label: &str, min_value: f32, max_value: f32, cold: f32, hot: f32, overheating: f32) -> Self {
+        let props = BarGaugeProps {
+            label: label.to_string(),
+            unit: "°C".to_string(),
+            min_value,
+            max_value,
+            danger_zone_start: Some(overheating),
+            warning_zone_start: Some(hot),
+            low_warning_zone_end: Some(cold),
+            graduations: GaugeGraduations {
+                major_tick_interval: 30.0,
+                minor_tick_interval: 10.0,
+                show_labels: true,
+                label_decimals: 0,
+            },
+            orientation: BarOrientation::Vertical,
+            segments: None,
+            style: BarGaugeStyle {
+                bar_color: [40, 110, 255, 255],
+                warning_color: [255, 176, 0, 255],
+                danger_color: [230, 40, 40, 255],
+                background_color: [60, 60, 60, 255],
+                border_color: [255, 255, 255, 255],
+                border_width: 1.5,
+                tick_width: 1.5,
+            },
+            label_font_size: 18.0,
+            value_font_size: 20.0,
+            value_decimals: 0,
+        };
+        Self {
+            gauge: BarGauge::new(props, theme),
+        }
+    }
+
+    pub fn set_value(&mut self, celsius: f32) {
+        self.gauge.set_value(celsius);
+    }
+}
+
+impl Widget for TemperatureGauge {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        self.gauge.render(canvas, rect, telemetry_state);
+    }
+    fn on_theme_change(&mut self, new_theme: &Theme, transition: ThemeTransition) {
+        self.gauge.on_theme_change(new_theme, transition);
+    }
+    fn update(&mut self, dt: Duration) {
+        self.gauge.update(dt);
+    }
+    fn preferred_size(&self, ctx: &LayoutContext) -> WidgetGeometry {
+        self.gauge.preferred_size(ctx)
+    }
+}
@@ -0,0 +1,218 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::timing::geo::{GeoPoint, LocalFrame, LocalPoint};
+use crate::timing::lap_timer::TracePoint;
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use std::time::Duration;
+
+/// Smallest extent shown, so a car standing still without an outline isn't zoomed in on
+const MIN_EXTENT_M: f64 = 200.0;
+/// Share of the rect the map fills, leaving room for the car marker at the edges
+const FILL: f64 = 0.9;
+/// Speed colours from slowest to fastest, blended across the speed range of the lap
+const SPEED_COLORS: [(u8, u8, u8); 3] = [(230, 40, 40), (255, 176, 0), (40, 200, 70)];
+/// Speeds are grouped into this many colours, with one path each
+const SPEED_BANDS: usize = 8;
+/// Narrowest speed range spread over the colours, in km/h
+const MIN_SPEED_RANGE_KPH: f64 = 20.0;
+const CAR_COLOR: (u8, u8, u8) = (40, 110, 255);
+
+/// The car's latest fix
+#[derive(Debug, Clone, Copy)]
+struct Car {
+    position: GeoPoint,
+    heading_deg: f32,
+}
+
+/// Everything the map shows, in GNSS coordinates
+struct MapData {
+    outline: Vec<GeoPoint>,
+    current_trace: Vec<TracePoint>,
+    car: Option<Car>,
+    ghost: Option<GeoPoint>,
+    speed_colors: bool,
+}
+
+/// Colour for `band` of `SPEED_BANDS`, slowest first
+fn speed_color(band: usize) -> Color {
+    let t = band as f32 / (SPEED_BANDS - 1) as f32 * (SPEED_COLORS.len() - 1) as f32;
+    let i = (t as usize).min(SPEED_COLORS.len() - 2);
+    let (a, b) = (SPEED_COLORS[i], SPEED_COLORS[i + 1]);
+    let t = t - i as f32;
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+    Color::rgb(lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2))
+}
+
+/// The circuit from above, north up, with the car's position and heading.
+///
+/// GNSS coordinates are projected onto a local tangent plane and scaled to fit
+/// the rect. The outline is the active track's, or else a lap driven this
+/// session. Optionally the lap in progress is drawn over it coloured by speed,
+/// and a hollow marker shows where the reference lap was at the same time into
+/// the lap.
+pub struct TrackMap {
+    theme: Theme,
+}
+
+impl TrackMap {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl Widget for TrackMap {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let map = {
+            let Ok(state) = telemetry_state.try_lock() else {
+                return;
+            };
+            let car = state.latest_racebox_data.as_ref().filter(|d| d.fix_ok).map(|d| Car {
+                position: GeoPoint::new(d.latitude, d.longitude),
+                heading_deg: d.heading_deg,
+            });
+            let timer = state.lap_timer.as_ref();
+            let ghost = match (state.settings.map_ghost, state.lap_delta.reference(), timer.and_then(|t| t.current_lap_time())) {
+                (true, Some(reference), Some(time)) => reference.position_at_time(time.as_secs_f64()),
+                _ => None,
+            };
+            MapData {
+                outline: state.map_outline(),
+                current_trace: timer.map(|t| t.current_trace().to_vec()).unwrap_or_default(),
+                car,
+                ghost,
+                speed_colors: state.settings.map_speed_colors,
+            }
+        };
+        let [r, g, b] = self.theme.text_color;
+        let text_color = Color::rgb(r, g, b);
+
+        // Project around the start of the outline, or the car when there is none
+        let Some(origin) = map.outline.first().copied().or(map.car.map(|car| car.position)) else {
+            let mut paint = Paint::color(Color::rgba(r, g, b, 120));
+            paint.set_font_size(rect.height * 0.06);
+            paint.set_text_align(Align::Center);
+            paint.set_text_baseline(Baseline::Middle);
+            let _ = canvas.fill_text(rect.center_x(), rect.center_y(), "NO POSITION", &paint);
+            return;
+        };
+        let frame = LocalFrame::new(origin);
+
+        // Fit the outline to the rect; without one, the lap so far and the car
+        let mut bounds: Vec<LocalPoint> = map.outline.iter().map(|p| frame.to_local(*p)).collect();
+        if bounds.is_empty() {
+            bounds.extend(map.current_trace.iter().map(|p| frame.to_local(p.position)));
+            bounds.extend(map.car.map(|car| frame.to_local(car.position)));
+        }
+        let (mut min_x, mut max_x, mut min_y, mut max_y) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        for p in &bounds {
+            (min_x, max_x) = (min_x.min(p.x), max_x.max(p.x));
+            (min_y, max_y) = (min_y.min(p.y), max_y.max(p.y));
+        }
+        let (mid_x, mid_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+        let extent_x = (max_x - min_x).max(MIN_EXTENT_M);
+        let extent_y = (max_y - min_y).max(MIN_EXTENT_M);
+        let scale = (rect.width as f64 / extent_x).min(rect.height as f64 / extent_y) * FILL;
+        let to_screen = |point: GeoPoint| {
+            let local = frame.to_local(point);
+            (
+                rect.center_x() + ((local.x - mid_x) * scale) as f32,
+                rect.center_y() - ((local.y - mid_y) * scale) as f32,
+            )
+        };
+
+        // Track outline
+        let line_width = (rect.width.min(rect.height) * 0.012).max(2.0);
+        if map.outline.len() >= 2 {
+            let mut outline = Path::new();
+            for (i, point) in map.outline.iter().enumerate() {
+                let (x, y) = to_screen(*point);
+                if i == 0 {
+                    outline.move_to(x, y);
+                } else {
+                    outline.line_to(x, y);
+                }
+            }
+            let mut paint = Paint::color(Color::rgba(r, g, b, if map.speed_colors { 60 } else { 160 }));
+            paint.set_line_width(line_width * 2.0);
+            canvas.stroke_path(&outline, &paint);
+        }
+
+        // Lap in progress, one path per speed band
+        if map.speed_colors && map.current_trace.len() >= 2 {
+            let speeds: Vec<Option<f64>> = map
+                .current_trace
+                .windows(2)
+                .map(|pair| {
+                    let dt = pair[1].time_s - pair[0].time_s;
+                    (dt > 0.0).then(|| (pair[1].distance_m - pair[0].distance_m) / dt * 3.6)
+                })
+                .collect();
+            let slowest = speeds.iter().flatten().copied().fold(f64::MAX, f64::min);
+            let fastest = speeds.iter().flatten().copied().fold(f64::MIN, f64::max);
+            let range = (fastest - slowest).max(MIN_SPEED_RANGE_KPH);
+            let mut bands: Vec<Path> = (0..SPEED_BANDS).map(|_| Path::new()).collect();
+            for (pair, speed) in map.current_trace.windows(2).zip(speeds) {
+                let Some(speed) = speed else {
+                    continue;
+                };
+                let band = (((speed - slowest) / range * SPEED_BANDS as f64) as usize).min(SPEED_BANDS - 1);
+                let (x0, y0) = to_screen(pair[0].position);
+                let (x1, y1) = to_screen(pair[1].position);
+                bands[band].move_to(x0, y0);
+                bands[band].line_to(x1, y1);
+            }
+            for (band, path) in bands.iter().enumerate() {
+                let mut paint = Paint::color(speed_color(band));
+                paint.set_line_width(line_width);
+                canvas.stroke_path(path, &paint);
+            }
+        }
+
+        // Reference lap, as a hollow marker
+        let marker_size = (rect.width.min(rect.height) * 0.035).max(6.0);
+        if let Some(ghost) = map.ghost {
+            let (x, y) = to_screen(ghost);
+            let mut marker = Path::new();
+            marker.circle(x, y, marker_size * 0.8);
+            let mut paint = Paint::color(text_color);
+            paint.set_line_width(2.0);
+            canvas.stroke_path(&marker, &paint);
+        }
+
+        // The car, as an arrow pointing along its heading
+        if let Some(car) = map.car {
+            let (x, y) = to_screen(car.position);
+            let heading = car.heading_deg.to_radians();
+            // Heading is clockwise from north; screen y grows downwards
+            let (forward_x, forward_y) = (heading.sin(), -heading.cos());
+            let (side_x, side_y) = (-forward_y, forward_x);
+            let mut marker = Path::new();
+            marker.move_to(x + forward_x * marker_size * 1.4, y + forward_y * marker_size * 1.4);
+            marker.line_to(
+                x - forward_x * marker_size + side_x * marker_size,
+                y - forward_y * marker_size + side_y * marker_size,
+            );
+            marker.line_to(x - forward_x * marker_size * 0.4, y - forward_y * marker_size * 0.4);
+            marker.line_to(
+                x - forward_x * marker_size - side_x * marker_size,
+                y - forward_y * marker_size - side_y * marker_size,
+            );
+            marker.close();
+            canvas.fill_path(&marker, &Paint::color(Color::rgb(CAR_COLOR.0, CAR_COLOR.1, CAR_COLOR.2)));
+            let mut outline = Paint::color(text_color);
+            outline.set_line_width(1.5);
+            canvas.stroke_path(&marker, &outline);
+        }
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 400.0, 400.0)
+    }
+}
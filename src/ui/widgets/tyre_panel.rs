@@ -0,0 +1,150 @@
+use femtovg::{Align, Baseline, Canvas, Color, Paint, Path, renderer::Renderer};
+use crate::telemetry::SharedTelemetryState;
+use crate::telemetry::tyres::CORNERS;
+use super::{Widget, WidgetGeometry, LayoutContext, ThemeTransition};
+use crate::ui::theme::Theme;
+use crate::vehicle::{TyreConfig, TyreWindow};
+use std::time::Duration;
+
+const COLD_COLOR: (u8, u8, u8) = (40, 110, 255);
+const OPTIMAL_COLOR: (u8, u8, u8) = (40, 200, 70);
+const HOT_COLOR: (u8, u8, u8) = (230, 40, 40);
+const LEAK_COLOR: (u8, u8, u8) = (255, 176, 0);
+/// Outside its window a reading shades fully to cold or hot over this fraction of the window's width
+const BLEND_BAND: f32 = 0.25;
+
+/// Green inside `window`, shading to blue below it and to red above it
+fn window_color(window: TyreWindow, value: f32) -> Color {
+    let band = (window.max - window.min) * BLEND_BAND;
+    let (target, t) = if value < window.min {
+        (COLD_COLOR, (window.min - value) / band)
+    } else if value > window.max {
+        (HOT_COLOR, (value - window.max) / band)
+    } else {
+        (OPTIMAL_COLOR, 0.0)
+    };
+    let t = t.min(1.0);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+    Color::rgb(
+        lerp(OPTIMAL_COLOR.0, target.0),
+        lerp(OPTIMAL_COLOR.1, target.1),
+        lerp(OPTIMAL_COLOR.2, target.2),
+    )
+}
+
+/// Readings for one corner
+#[derive(Debug, Clone, Copy)]
+struct Corner {
+    pressure_bar: Option<f32>,
+    temp_c: Option<f32>,
+    leaking: bool,
+}
+
+/// The car seen from above, with each tyre coloured by its temperature and its
+/// pressure and temperature alongside, coloured against the windows in the
+/// vehicle config.
+///
+/// A tyre suspected of a slow leak is outlined and labelled. A corner whose
+/// sensors report nothing is left blank.
+pub struct TyrePanel {
+    theme: Theme,
+}
+
+impl TyrePanel {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl Widget for TyrePanel {
+    fn render<R: Renderer>(&self, canvas: &mut Canvas<R>, rect: WidgetGeometry, telemetry_state: &SharedTelemetryState) {
+        let (corners, config): ([Corner; 4], TyreConfig) = {
+            let Ok(state) = telemetry_state.try_lock() else {
+                return;
+            };
+            let esp32 = &state.latest_esp32_data;
+            let corners = std::array::from_fn(|i| Corner {
+                pressure_bar: esp32.tyre_pressure_bar(i),
+                temp_c: esp32.tyre_temp_c(i),
+                leaking: state.tyres.leaking(i),
+            });
+            (corners, state.vehicle.tyres.clone())
+        };
+        let [r, g, b] = self.theme.text_color;
+        let text_color = Color::rgb(r, g, b);
+        let dim_color = Color::rgba(r, g, b, 60);
+
+        // Car body
+        let body_width = rect.width * 0.24;
+        let body_height = rect.height * 0.9;
+        let body_x = rect.center_x() - body_width / 2.0;
+        let body_y = rect.center_y() - body_height / 2.0;
+        let mut body = Path::new();
+        body.rounded_rect(body_x, body_y, body_width, body_height, body_width * 0.3);
+        let mut outline = Paint::color(dim_color);
+        outline.set_line_width(2.0);
+        canvas.stroke_path(&body, &outline);
+
+        let tyre_width = body_width * 0.24;
+        let tyre_height = body_height * 0.2;
+        let font_size = tyre_height * 0.42;
+        for (i, corner) in corners.iter().enumerate() {
+            let left = i.is_multiple_of(2);
+            let x = if left { body_x - tyre_width * 0.5 } else { body_x + body_width - tyre_width * 0.5 };
+            let y = body_y + body_height * if i < 2 { 0.12 } else { 0.68 };
+            let mut tyre = Path::new();
+            tyre.rounded_rect(x, y, tyre_width, tyre_height, tyre_width * 0.2);
+            if let Some(temp) = corner.temp_c {
+                canvas.fill_path(&tyre, &Paint::color(window_color(config.temp_c, temp)));
+            }
+            let tyre_outline = if corner.leaking {
+                let mut paint = Paint::color(Color::rgb(LEAK_COLOR.0, LEAK_COLOR.1, LEAK_COLOR.2));
+                paint.set_line_width(4.0);
+                paint
+            } else {
+                outline.clone()
+            };
+            canvas.stroke_path(&tyre, &tyre_outline);
+
+            if corner.pressure_bar.is_none() && corner.temp_c.is_none() {
+                continue;
+            }
+            // Readings beside the tyre, on the outside of the car
+            let (text_x, align) = if left {
+                (x - tyre_width * 0.5, Align::Right)
+            } else {
+                (x + tyre_width * 1.5, Align::Left)
+            };
+            let mut paint = Paint::color(text_color);
+            paint.set_text_align(align);
+            paint.set_text_baseline(Baseline::Middle);
+            paint.set_font_size(font_size * 0.6);
+            let _ = canvas.fill_text(text_x, y - font_size * 0.3, CORNERS[i], &paint);
+            if let Some(pressure) = corner.pressure_bar {
+                paint.set_color(window_color(config.pressure_bar, pressure));
+                paint.set_font_size(font_size);
+                let _ = canvas.fill_text(text_x, y + tyre_height * 0.25, format!("{:.2} bar", pressure), &paint);
+            }
+            if let Some(temp) = corner.temp_c {
+                paint.set_color(window_color(config.temp_c, temp));
+                paint.set_font_size(font_size * 0.8);
+                let _ = canvas.fill_text(text_x, y + tyre_height * 0.75, format!("{:.0} °C", temp), &paint);
+            }
+            if corner.leaking {
+                paint.set_color(Color::rgb(LEAK_COLOR.0, LEAK_COLOR.1, LEAK_COLOR.2));
+                paint.set_font_size(font_size * 0.7);
+                let _ = canvas.fill_text(text_x, y + tyre_height * 1.15, "SLOW LEAK", &paint);
+            }
+        }
+    }
+
+    fn on_theme_change(&mut self, new_theme: &Theme, _transition: ThemeTransition) {
+        self.theme = new_theme.clone();
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn preferred_size(&self, _ctx: &LayoutContext) -> WidgetGeometry {
+        WidgetGeometry::new(0.0, 0.0, 400.0, 400.0)
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const VEHICLE_FILE: &str = "vehicle.yml";
+/// Below this speed the gear can't be told from the RPM/speed ratio
+const GEAR_MIN_SPEED_KPH: f32 = 5.0;
+/// How far the RPM/speed ratio may be from a gear's and still count as that gear
+const GEAR_RATIO_TOLERANCE: f32 = 0.08;
+
+#[derive(Error, Debug)]
+pub enum VehicleError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid vehicle config: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Fuel calibration needs at least two points with different readings")]
+    InvalidCalibration,
+
+    #[error("Drivetrain needs at least one gear ratio and a positive tyre circumference")]
+    InvalidDrivetrain,
+
+    #[error("Tyre windows need min below max, and the leak threshold must be positive")]
+    InvalidTyres,
+
+    #[error("Stint limits need pit_window_laps of at least 0 and a positive max_stint_min")]
+    InvalidStint,
+}
+
+/// One point of the fuel sender calibration: ADC reading and the fuel in the tank
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FuelCalibrationPoint {
+    pub raw: u16,
+    pub litres: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuelConfig {
+    pub tank_l: f32,
+    /// Fuel that should be left in the tank at the end of a stint
+    #[serde(default)]
+    pub reserve_l: f32,
+    /// ADC reading to litres, interpolated linearly between points
+    pub calibration: Vec<FuelCalibrationPoint>,
+}
+
+impl FuelConfig {
+    /// Litres in the tank for a raw `fuel_level` reading, held at the ends of the calibration
+    pub fn litres(&self, raw: u16) -> Option<f32> {
+        let after = self.calibration.partition_point(|p| p.raw < raw);
+        let (a, b) = match (after.checked_sub(1), self.calibration.get(after)) {
+            (Some(before), Some(b)) => (self.calibration[before], *b),
+            (None, Some(first)) => return Some(first.litres),
+            (Some(_), None) => return self.calibration.last().map(|p| p.litres),
+            (None, None) => return None,
+        };
+        let t = (raw - a.raw) as f32 / (b.raw - a.raw) as f32;
+        Some((a.litres + (b.litres - a.litres) * t).clamp(0.0, self.tank_l))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StintConfig {
+    /// The pit window opens when the fuel above the reserve lasts this many laps or fewer
+    pub pit_window_laps: f32,
+    /// Longest a stint may last, e.g. a track day session or driver change rule
+    #[serde(default)]
+    pub max_stint_min: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrivetrainConfig {
+    /// Gearbox ratios, first gear first
+    pub gear_ratios: Vec<f32>,
+    pub final_drive: f32,
+    /// Rolling circumference of the driven wheels
+    pub tyre_circumference_m: f32,
+    /// Suggest a downshift below this RPM, if the lower gear has room
+    pub downshift_rpm: f32,
+}
+
+impl Default for DrivetrainConfig {
+    /// VX220 Turbo gearbox on 225/45 R17 rear tyres
+    fn default() -> Self {
+        Self {
+            gear_ratios: vec![3.58, 2.02, 1.35, 0.98, 0.81],
+            final_drive: 3.63,
+            tyre_circumference_m: 1.99,
+            downshift_rpm: 3000.0,
+        }
+    }
+}
+
+impl DrivetrainConfig {
+    /// Engine RPM for `speed_kph` in forward gear `gear` (1 for first)
+    pub fn rpm_in_gear(&self, gear: u8, speed_kph: f32) -> Option<f32> {
+        let ratio = self.gear_ratios.get((gear as usize).checked_sub(1)?)?;
+        let wheel_rpm = speed_kph / 3.6 / self.tyre_circumference_m * 60.0;
+        Some(wheel_rpm * ratio * self.final_drive)
+    }
+
+    /// The forward gear that matches the ratio of engine RPM to road speed, or
+    /// `None` when stationary or with the clutch slipping or disengaged
+    pub fn gear_from_rpm(&self, rpm: f32, speed_kph: f32) -> Option<u8> {
+        if speed_kph < GEAR_MIN_SPEED_KPH {
+            return None;
+        }
+        (1..=self.gear_ratios.len() as u8)
+            .filter_map(|gear| Some((gear, (rpm / self.rpm_in_gear(gear, speed_kph)? - 1.0).abs())))
+            .filter(|(_, error)| *error <= GEAR_RATIO_TOLERANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(gear, _)| gear)
+    }
+
+    pub fn gears(&self) -> u8 {
+        self.gear_ratios.len() as u8
+    }
+}
+
+/// Range a tyre reading should be in: below `min` is cold, above `max` hot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TyreWindow {
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TyreConfig {
+    /// Hot pressure window
+    pub pressure_bar: TyreWindow,
+    pub temp_c: TyreWindow,
+    /// Flag a slow leak when a tyre loses this much pressure relative to the other three
+    pub leak_drop_bar: f32,
+}
+
+impl Default for TyreConfig {
+    /// Road tyres on track
+    fn default() -> Self {
+        Self {
+            pressure_bar: TyreWindow { min: 1.9, max: 2.3 },
+            temp_c: TyreWindow { min: 60.0, max: 95.0 },
+            leak_drop_bar: 0.15,
+        }
+    }
+}
+
+/// Vehicle settings, loaded from `assets/vehicle.yml`.
+///
+/// Example YAML format:
+///
+/// ```yaml
+/// fuel:
+///   tank_l: 41.5
+///   reserve_l: 3.0
+///   calibration:
+///     - { raw: 300, litres: 0.0 }
+///     - { raw: 3800, litres: 41.5 }
+/// stint:
+///   pit_window_laps: 3
+///   max_stint_min: 25
+/// drivetrain:
+///   gear_ratios: [3.58, 2.02, 1.35, 0.98, 0.81]
+///   final_drive: 3.63
+///   tyre_circumference_m: 1.99
+///   downshift_rpm: 3000
+/// tyres:
+///   pressure_bar: { min: 1.9, max: 2.3 }
+///   temp_c: { min: 60, max: 95 }
+///   leak_drop_bar: 0.15
+/// ```
+///
+/// The calibration maps the ESP32's 12-bit `fuel_level` reading to litres and
+/// can have any number of points, in any order. `reserve_l`, `max_stint_min`
+/// and the `drivetrain` and `tyres` sections are optional.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleConfig {
+    pub fuel: FuelConfig,
+    pub stint: StintConfig,
+    #[serde(default)]
+    pub drivetrain: DrivetrainConfig,
+    #[serde(default)]
+    pub tyres: TyreConfig,
+}
+
+impl Default for VehicleConfig {
+    /// A VX220 tank with a linear sender, until a calibration is loaded
+    fn default() -> Self {
+        Self {
+            fuel: FuelConfig {
+                tank_l: 41.5,
+                reserve_l: 3.0,
+                calibration: vec![
+                    FuelCalibrationPoint { raw: 0, litres: 0.0 },
+                    FuelCalibrationPoint { raw: 4095, litres: 41.5 },
+                ],
+            },
+            stint: StintConfig {
+                pit_window_laps: 3.0,
+                max_stint_min: None,
+            },
+            drivetrain: DrivetrainConfig::default(),
+            tyres: TyreConfig::default(),
+        }
+    }
+}
+
+impl VehicleConfig {
+    /// `assets/vehicle.yml` next to the executable, or in the current directory
+    pub fn default_path() -> PathBuf {
+        if let Ok(exe_path) = env::current_exe()
+            && let Some(exe_dir) = exe_path.parent()
+        {
+            let path = exe_dir.join("assets").join(VEHICLE_FILE);
+            if path.exists() {
+                return path;
+            }
+        }
+        Path::new("assets").join(VEHICLE_FILE)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, VehicleError> {
+        let yaml = fs::read_to_string(path)?;
+        let mut config: Self = serde_yaml::from_str(&yaml)?;
+        config.fuel.calibration.sort_by_key(|p| p.raw);
+        config.fuel.calibration.dedup_by_key(|p| p.raw);
+        if config.fuel.calibration.len() < 2 {
+            return Err(VehicleError::InvalidCalibration);
+        }
+        if config.drivetrain.gear_ratios.is_empty() || config.drivetrain.tyre_circumference_m <= 0.0 {
+            return Err(VehicleError::InvalidDrivetrain);
+        }
+        let tyres = &config.tyres;
+        if tyres.pressure_bar.min >= tyres.pressure_bar.max || tyres.temp_c.min >= tyres.temp_c.max || tyres.leak_drop_bar <= 0.0 {
+            return Err(VehicleError::InvalidTyres);
+        }
+        let stint = &config.stint;
+        // Still finite once converted to seconds for the stint timer
+        let valid_max_stint = |min: f32| min > 0.0 && (min * 60.0).is_finite();
+        if !(stint.pit_window_laps.is_finite() && stint.pit_window_laps >= 0.0)
+            || stint.max_stint_min.is_some_and(|min| !valid_max_stint(min))
+        {
+            return Err(VehicleError::InvalidStint);
+        }
+        Ok(config)
+    }
+
+    /// Load the default config, falling back to an uncalibrated linear fuel sender
+    pub fn load_default() -> Self {
+        let path = Self::default_path();
+        match Self::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                crate::telemetry_log!(log::Level::Warn, "Vehicle config {} not loaded: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+}